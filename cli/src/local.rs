@@ -0,0 +1,165 @@
+use std::io;
+use linefeed::{Interface, ReadResult};
+use game_rules::{Game, GameRules, PlayerMove};
+
+use crate::{current_timestamp, split_first_word};
+
+/*===================================================
+=            Offline hot-seat game mode            =
+===================================================*/
+
+// Players are named after their pieces. Player 2 plays X and goes first.
+const PLAYER_1: &str = "O";
+const PLAYER_2: &str = "X";
+
+static COMMANDS: &[(&str, &str)] = &[
+    ("help",             "Displays this the help page"),
+    ("moves",            "Display the set of moves this game supports"),
+    ("make_move",        "Make a move for the player whose turn it is, usage: make_move <move_json>"),
+    ("hint",             "Ask the computer for the best move the current player can make"),
+    ("takeback",         "Take back the last move, with both players agreeing to it"),
+    ("new_game",         "Clear the board and start a new game"),
+    ("exit",             "Exit this CLI. Local games are not saved."),
+];
+
+/// Runs a game between two players sharing this terminal. The rules come straight from the
+/// game_rules crate so moves are validated exactly as the zome would validate them.
+pub fn run<R: GameRules>(
+    width: Option<usize>,
+    height: Option<usize>,
+    win_length: Option<usize>,
+) -> io::Result<()> {
+    let game = Game {
+        player_1: PLAYER_1.into(),
+        player_2: PLAYER_2.into(),
+        created_at: current_timestamp(),
+        width: width.unwrap_or(R::DEFAULT_WIDTH),
+        height: height.unwrap_or(R::DEFAULT_HEIGHT),
+        win_length: win_length.unwrap_or(R::DEFAULT_WIN_LENGTH),
+        // players sharing a terminal can keep their own time
+        time_control: None,
+        player_1_moves_first: false,
+    };
+    if let Err(e) = R::is_valid_game(&game) {
+        println!("Error: {}", e);
+        return Ok(());
+    }
+
+    let interface = Interface::new("Holochain generic game")?;
+
+    println!();
+    println!("{}", "#".repeat(70));
+    println!("Local hot-seat game, no conductor required.");
+    println!("Enter \"help\" for a list of commands.");
+    if let Some(example) = R::describe_moves().first() {
+        println!(
+            "Take turns entering moves, e.g. make_move {}",
+            serde_json::to_string(example).unwrap_or_default()
+        );
+    }
+    println!("Press Ctrl-D or enter \"exit\" to exit.");
+    println!("{}", "#".repeat(70));
+    println!();
+
+    let mut state = R::initial(&game);
+    println!("{}", R::render(&game, &state, None));
+    interface.set_prompt(&prompt::<R>(&game, &state))?;
+
+    while let ReadResult::Input(line) = interface.read_line()? {
+        if !line.trim().is_empty() {
+            interface.add_history_unique(line.clone());
+        }
+
+        let (cmd, args) = split_first_word(&line);
+
+        let result: Result<(), String> = match cmd {
+            "help" => {
+                println!("Local game commands:");
+                println!();
+                for &(cmd, help) in COMMANDS {
+                    println!("  {:15} - {}", cmd, help);
+                    println!();
+                }
+                Ok(())
+            }
+            "moves" => {
+                println!("The valid moves are:");
+                R::describe_moves().iter().for_each(|move_type| {
+                    println!("- {}", serde_json::to_string(move_type).unwrap_or_default());
+                });
+                println!();
+                Ok(())
+            }
+            "make_move" => serde_json::from_str(args)
+                .map_err(|e| format!("Could not read move: {}", e))
+                .and_then(|move_type| {
+                    let next_move = PlayerMove {
+                        author: R::next_player(&game, &state).into(),
+                        move_type,
+                        timestamp: current_timestamp(),
+                    };
+                    R::is_valid(&game, &state, &next_move)?;
+                    state = R::evolve(&game, &state, &next_move);
+                    Ok(())
+                }),
+            "takeback" => takeback::<R>(&game, &state).map(|taken_back| state = taken_back),
+            "hint" => match R::best_move(&game, &state) {
+                Some(move_type) => {
+                    println!(
+                        "Suggested move for {}: make_move {}",
+                        R::next_player(&game, &state),
+                        serde_json::to_string(&move_type).unwrap_or_default()
+                    );
+                    Ok(())
+                }
+                None if R::outcome(&state).is_over() => Err("This game has ended".into()),
+                None => Err("There is no computer player for this game".into()),
+            },
+            "new_game" => {
+                state = R::initial(&game);
+                Ok(())
+            }
+            "exit" => {
+                println!("Bye!");
+                break;
+            }
+            _ => Err("Invalid command!".into()),
+        };
+
+        if let Err(e) = result {
+            println!("Error: {}", e)
+        }
+
+        println!("{}", R::render(&game, &state, None));
+        interface.set_prompt(&prompt::<R>(&game, &state))?;
+    }
+    Ok(())
+}
+
+/// Both players share this terminal, so the last mover asks for the takeback and the other player
+/// agrees to it straight away
+fn takeback<R: GameRules>(game: &Game, state: &R::State) -> Result<R::State, String> {
+    let next_player = R::next_player(game, state);
+    [(game.opponent(next_player), "RequestTakeback"), (next_player, "AcceptTakeback")]
+        .iter()
+        .try_fold(state.clone(), |state, &(author, move_type)| {
+            let next_move = PlayerMove {
+                author: author.into(),
+                move_type: serde_json::from_value(serde_json::Value::from(move_type))
+                    .map_err(|_| "This game does not support takebacks".to_string())?,
+                timestamp: current_timestamp(),
+            };
+            R::is_valid(game, &state, &next_move)?;
+            Ok(R::evolve(game, &state, &next_move))
+        })
+}
+
+fn prompt<R: GameRules>(game: &Game, state: &R::State) -> String {
+    if R::outcome(state).is_over() {
+        "Game over> ".into()
+    } else {
+        format!("{} to play> ", R::next_player(game, state))
+    }
+}
+
+/*=====  End of Offline hot-seat game mode  ======*/
@@ -4,25 +4,57 @@ use std::time::{self, SystemTime, UNIX_EPOCH};
 use std::thread;
 use serde_json::json;
 use structopt::StructOpt;
-use linefeed::{Interface, ReadResult};
+use linefeed::{DefaultTerminal, Interface, ReadResult, Terminal};
+use linefeed::terminal::{RawRead, SignalSet};
+use game_rules::{clock::format_millis, with_rules, Clock, GameError, Ruleset, TimeControl};
+
+mod local;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "example", about = "An example of StructOpt usage.")]
 struct Cli {
 	/// Url to connect to the running conductor HTTP port (e.g. http://localhost:3000)
-	url: reqwest::Url,
+	#[structopt(required_unless = "local")]
+	url: Option<reqwest::Url>,
 	/// This is the instance ID in the conductor that is running the game on the given port (e.g gameInstance)
-	instance: String,
+	#[structopt(required_unless = "local")]
+	instance: Option<String>,
+	/// Play a hot-seat game on this terminal without connecting to a conductor
+	#[structopt(long = "local")]
+	local: bool,
+	/// Which game to play, tic_tac_toe or connect_four
+	#[structopt(long = "game", default_value = "tic_tac_toe")]
+	game: Ruleset,
+	/// Time control for new games, either seconds per move (e.g. 30) or a Fischer clock of total seconds plus an increment per move (e.g. 300+5)
+	#[structopt(long = "time-control")]
+	time_control: Option<TimeControl>,
+	/// Width of the board for a local game or a proposal, defaults to the usual board for the game
+	#[structopt(long = "width")]
+	width: Option<usize>,
+	/// Height of the board for a local game or a proposal, defaults to the usual board for the game
+	#[structopt(long = "height")]
+	height: Option<usize>,
+	/// Number of pieces in a row needed to win a local game or a proposal, defaults to the usual rule for the game
+	#[structopt(long = "win-length")]
+	win_length: Option<usize>,
 }
 
 static COMMANDS: &[(&str, &str)] = &[
     ("help",             "Displays this the help page"),
     ("join_game",        "Set the game to make moves against, usage: join_game <game_address>"),
-    ("new_game",         "Create a new game to play with an opponent, usage: new_game <opponent_address>"),
+    ("games",            "List your games, or join one of them by its number, usage: games [<index>]"),
+    ("new_game",         "Create a new game of --game to play with an opponent, usage: new_game <opponent_address> [<width> <height> <win_length>]"),
     ("moves",            "Display the set of moves this game supports"),
     ("make_move",        "Make a move in this game, usage: make_move <move_json>"),
+    ("hint",             "Ask the computer for the best move you can make in this game"),
+    ("ai_game",          "Let the computer play your side of this game until it ends"),
+    ("takeback",         "Ask to take back your last move, or agree to your opponent's request to take back theirs"),
+    ("watch",            "Follow any game as a spectator, showing each move as it is made until you press a key. Usage: watch <game_address>"),
  
-    ("create_proposal",  "Publicly publish that you are looking for someone to play with. Usage: post_propoal <message>"),
+    ("stats",            "Show the wins, losses and draws of a player, or your own. Usage: stats [<agent_address>]"),
+    ("leaderboard",      "Show every player who has finished a game, ranked by Elo rating"),
+
+    ("create_proposal",  "Publicly publish that you are looking for someone to play a game of --game with. Usage: create_proposal [--for <agent_address>] [--expires <minutes>] [--max <games>] [--you-first] <message>"),
     ("accept_proposal",   "Accept a propsal. This will start a new game. Usage: accept_proposal <proposal_hash>"),
     ("get_proposals",    "Get all of the public proposals that are current"),
     ("check_responses",  "Given a proposal hash find the responses. Usage: check_responses <proposal_hash>"),
@@ -34,19 +66,36 @@ static COMMANDS: &[(&str, &str)] = &[
 fn main() -> io::Result<()> {
     let cli = Cli::from_args();
 
+    if cli.local {
+        return with_rules!(cli.game, R => local::run::<R>(cli.width, cli.height, cli.win_length));
+    }
+    let (url, instance) = match (cli.url, cli.instance) {
+        (Some(url), Some(instance)) => (url, instance),
+        _ => {
+            println!("A conductor url and instance are required unless playing with --local");
+            return Ok(());
+        }
+    };
+
     // create the functions required for playing the game
-    let whoami = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "whoami".into());
-    let valid_moves = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_valid_moves".into());
-    let make_move = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "make_move".into());
-    let create_game = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "create_game".into());
-    let render_game = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "render_state".into());
+    let whoami = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "whoami".into());
+    let valid_moves = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "get_valid_moves".into());
+    let make_move = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "make_move".into());
+    let create_game = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "create_game".into());
+    let render_game = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "render_state".into());
+    let get_state = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "get_state".into());
+    let get_best_move = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "get_best_move".into());
+    let list_my_games = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "list_my_games".into());
+    let get_player_stats = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "get_player_stats".into());
+    let get_leaderboard = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "get_leaderboard".into());
 
     // matchmaking funcs
-    let create_proposal = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "create_proposal".into());
-    let get_proposals = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_proposals".into());
-    let accept_proposal = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "accept_proposal".into());
-    let check_responses = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "check_responses".into());
-    let _remove_proposal = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "remove_proposal".into());
+    let create_proposal = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "create_proposal".into());
+    let get_proposals = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "get_proposals".into());
+    let accept_proposal = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "accept_proposal".into());
+    let check_responses = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "check_responses".into());
+    let get_notifications = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "get_notifications".into());
+    let remove_proposal = holochain_call_generator(url.clone(), instance.clone(), "main".into(), "remove_proposal".into());
    
 
     let interface = Interface::new("Holochain generic game")?;
@@ -57,20 +106,23 @@ fn main() -> io::Result<()> {
     println!("CLI interface for games written using the Holochain Generic Game framework.");
     println!("Enter \"help\" for a list of commands.");
     println!("Use \"create_game <agent_id>\" or \"join_game <game_address>\" to start or join a game.");
+    println!("Use \"games\" to find the games you are already playing.");
     println!("Press Ctrl-D or enter \"quit\" to exit.");
     println!("{}", repeat('#').take(70).collect::<String>());
     println!("");
     println!("");
 
-    match whoami(json!({})) {
+    let agent_addr = match whoami(json!({})) {
     	Ok(agent_addr) => {
     		println!("Your agent address is {}\n\nSend this to other players so they can invite you to a game.", agent_addr);
+    		agent_addr
     	},
-    	Err(_e) => {
-    		println!("No holochain instance named {} running on {}. Check the conductor is running and the instanceId in the conductor config is correct.", cli.instance, cli.url);
+    	Err(e) => {
+    		println!("No holochain instance named {} running on {}. Check the conductor is running and the instanceId in the conductor config is correct.", instance, url);
+    		println!("{}", e);
     		return Ok(());
     	}
-    }
+    };
 
     println!("");
     println!("");
@@ -107,24 +159,60 @@ fn main() -> io::Result<()> {
             		Err("argument must be a valid address".into())
             	}
             }
+            "games" => {
+            	list_my_games(json!({})).and_then(|games| {
+            		let games = games.as_array().cloned().unwrap_or_default();
+            		if args.is_empty() {
+            			if games.is_empty() {
+            				println!("You have no games yet, start one with \"new_game\" or \"accept_proposal\"");
+            			}
+            			for (index, game) in games.iter().enumerate() {
+            				println!("[{}] {} against {}, {}, last played {}",
+            					index,
+            					game["address"].as_str().unwrap_or_default(),
+            					game["opponent"].as_str().unwrap_or_default(),
+            					status_label(&game["status"]),
+            					describe_age(game["last_activity"].as_u64().unwrap_or_default()));
+            			}
+            			println!();
+            			Ok(())
+            		} else {
+            			let game = args.parse::<usize>().ok().and_then(|index| games.get(index))
+            				.ok_or_else(|| format!("{} is not the number of one of your games", args))?;
+            			let address = game["address"].as_str().unwrap_or_default();
+            			println!("Setting current game hash to {}", address);
+            			current_game = Some(address.into());
+            			Ok(())
+            		}
+            	})
+            }
             "new_game" => {
-            	if is_agent_addr(args) {
+            	let (opponent, board_args) = split_first_word(args);
+            	let board: Vec<usize> = board_args.split_whitespace().filter_map(|n| n.parse().ok()).collect();
+            	if !is_agent_addr(opponent) {
+            		Err("argument must be valid agent address of an opponent.".into())
+            	} else if !board.is_empty() && board.len() != 3 {
+            		Err("board size must be given as <width> <height> <win_length>".into())
+            	} else {
             		let result = create_game(json!({
-            			"opponent": args,
-            			"timestamp": current_timestamp()
+            			"opponent": opponent,
+            			"timestamp": current_timestamp(),
+            			"ruleset": cli.game,
+            			"time_control": cli.time_control,
+            			"width": board.first(),
+            			"height": board.get(1),
+            			"win_length": board.get(2)
             		}));
                     result.map(|result| {
                         current_game = result.as_str().map(|s| s.to_string());
                     })
-            	} else {
-            		Err("argument must be valid agent address of an opponent.".into())
             	}
             }
             "moves" => {
-            	valid_moves(json!({})).map(|result| {
+            	valid_moves(json!({"ruleset": cli.game})).map(|result| {
 	            	println!("The valid moves are:");
-	            	result.as_array().unwrap()
-	            	.iter()
+	            	result.as_array().iter()
+	            	.flat_map(|moves| moves.iter())
 	            	.for_each(|elem| {
 	            		println!("- {}", elem);
 	            	});
@@ -153,42 +241,154 @@ fn main() -> io::Result<()> {
             		Err("No game set to make moves on. use the \"join_game\" command.".into())
             	}
             },
+            "hint" => {
+            	if let Some(current_game) = current_game.clone() {
+            		get_best_move(json!({"game_address": current_game})).map(|move_type| {
+            			println!("Suggested move: make_move {}", move_type);
+            		})
+            	} else {
+            		Err("No game set to get a hint for. use the \"join_game\" command.".into())
+            	}
+            },
+            "takeback" => {
+            	if let Some(current_game) = current_game.clone() {
+            		get_state(json!({"game_address": current_game})).and_then(|state| {
+            			let requested_by = &state["takeback_requested_by"];
+            			let move_type = if requested_by.is_null() || requested_by == &agent_addr {
+            				"RequestTakeback"
+            			} else {
+            				"AcceptTakeback"
+            			};
+            			make_move(json!({
+            				"new_move": {
+            					"game": current_game,
+            					"move_type": move_type,
+            					"timestamp": current_timestamp()
+            				}
+            			}))
+            		}).map(|_| {
+            			println!("Takeback sent");
+            			println!("Waiting for gossip...");
+            			thread::sleep(time::Duration::from_millis(4000));
+            			println!("OK!")
+            		})
+            	} else {
+            		Err("No game set to take a move back in. use the \"join_game\" command.".into())
+            	}
+            },
+            "ai_game" => {
+            	if let Some(current_game) = current_game.clone() {
+            		println!("The computer is playing your side of this game. Press Ctrl-C to stop.");
+            		let play = || -> Result<(), String> {
+            			let mut waiting = false;
+            			while get_state(json!({"game_address": current_game}))?["outcome"] == "InProgress" {
+            				match get_best_move(json!({"game_address": current_game})) {
+            					Ok(move_type) => {
+            						println!("Computer plays {}", move_type);
+            						make_move(json!({
+            							"new_move": {
+            								"game": current_game,
+            								"move_type": move_type,
+            								"timestamp": current_timestamp()
+            							}
+            						}))?;
+            						waiting = false;
+            					},
+            					Err(e) if e != GameError::not_your_turn().to_string() => return Err(e),
+            					Err(_) if !waiting => {
+            						println!("Waiting for your opponent to move...");
+            						waiting = true;
+            					},
+            					Err(_) => {}
+            				}
+            				// give the last move time to gossip before looking again
+            				thread::sleep(time::Duration::from_millis(4000));
+            			}
+            			Ok(())
+            		};
+            		play()
+            	} else {
+            		Err("No game set for the computer to play. use the \"join_game\" command.".into())
+            	}
+            },
+            "watch" => {
+            	if is_hash(args) {
+            		watch_game(&*get_state, &*render_game, args)
+            	} else {
+            		Err("argument must be a valid address".into())
+            	}
+            },
+            "stats" => {
+            	let agent = if args.is_empty() { agent_addr.clone() } else { json!(args) };
+            	if !agent.as_str().map(is_agent_addr).unwrap_or(false) {
+            		Err("argument must be a valid agent address".into())
+            	} else {
+            		get_player_stats(json!({"agent": agent})).map(|stats| {
+            			println!("{}", describe_stats(&stats));
+            			println!();
+            		})
+            	}
+            },
+            "leaderboard" => {
+            	get_leaderboard(json!({})).map(|leaderboard| {
+            		println!("Leaderboard:\n");
+            		leaderboard.as_array().iter().flat_map(|entries| entries.iter()).enumerate().for_each(|(rank, entry)| {
+            			println!("{:>3}. {:>5.0}  {}{}  {}",
+            				rank + 1,
+            				entry["rating"].as_f64().unwrap_or_default(),
+            				entry["agent"].as_str().unwrap_or_default(),
+            				if entry["agent"] == agent_addr { " (you)" } else { "" },
+            				describe_stats(&entry["stats"]));
+            		});
+            		println!();
+            	})
+            },
             "create_proposal" => {
-                println!("creating proposal with message {:?}", args);
-                let result = create_proposal(json!({"message": args}));
-                println!("Create result: {:?}", result);
-                Ok(())
+                let settings = json!({
+                    "ruleset": cli.game,
+                    "width": cli.width,
+                    "height": cli.height,
+                    "win_length": cli.win_length,
+                    "time_control": cli.time_control,
+                    "acceptor_moves_first": false
+                });
+                proposal_options(args, settings).and_then(|(mut proposal, message)| {
+                    println!("creating proposal with message {:?}", message);
+                    proposal["message"] = json!(message);
+                    create_proposal(proposal)
+                }).map(|address| {
+                    println!("Proposal created with address: {}", address);
+                })
             },
             "get_proposals" => {
-                let result = get_proposals(json!({})).unwrap();
-                println!("Current game proposals: \n");
-                result.as_array().unwrap().iter().for_each(|r| {
-                    println!("[{}] : {{ Agent: {}, Message: {} }}", r["address"].as_str().unwrap(), r["entry"]["agent"], r["entry"]["message"]);
-                });
-                println!("\n");
-                Ok(())
+                get_proposals(json!({})).map(|result| {
+                    println!("Current game proposals: \n");
+                    result.as_array().iter().flat_map(|proposals| proposals.iter()).for_each(|r| {
+                        println!("[{}] : {{ Agent: {}, Message: {} }}", r["address"].as_str().unwrap_or_default(), r["entry"]["agent"], r["entry"]["message"]);
+                        println!("    {}", describe_proposal(&r["entry"], &agent_addr));
+                    });
+                    println!("\n");
+                })
             },
             "accept_proposal" => {
                 accept_proposal(json!({"proposal_addr": args, "created_at": current_timestamp()})).map(|game_addr| {
                     println!("Proposal accepted. Game created with address: {}", game_addr);
-                    current_game = Some(game_addr.as_str().unwrap().into());
+                    current_game = game_addr.as_str().map(|s| s.to_string());
                 })
             },
             "check_responses" => {
-                let result = check_responses(json!({"proposal_addr": args})).unwrap();
-                println!("Proposal has the following responses: \n");
-                result.as_array().unwrap().iter().for_each(|response| {
-                    println!("[{}] : Agent: {}", response["address"], response["entry"]["player_1"]);
-                });
-                println!("use \"join_game\" with any of the listed addresses to join: \n");
-                Ok(())            
+                check_responses(json!({"proposal_addr": args})).map(|result| {
+                    println!("Proposal has the following responses: \n");
+                    result.as_array().iter().flat_map(|responses| responses.iter()).for_each(|response| {
+                        println!("[{}] : Agent: {}", response["address"], response["entry"]["player_1"]);
+                    });
+                    println!("use \"join_game\" with any of the listed addresses to join: \n");
+                })
             },
             "remove_proposal" => {
-                println!("NOT IMPLEMENTED - this has been disabled until deletion but is fixed.");
-                Ok(())
-                // remove_proposal(json!({"proposal_addr": args})).map(|_| {
-                //     println!("Proposal successfully marked as deleted\n");
-                // })
+                remove_proposal(json!({"proposal_addr": args})).map(|_| {
+                    println!("Proposal successfully marked as deleted\n");
+                })
             },
             "exit" => {
             	if let Some(current_game) = current_game.clone() {
@@ -206,14 +406,38 @@ fn main() -> io::Result<()> {
             println!("Error: {}", e)
         }
 
+		// tell the proposer about games started from their proposals, joining the first if they are not playing yet
+		if let Ok(notifications) = get_notifications(json!({})) {
+			for notification in notifications.as_array().iter().flat_map(|notifications| notifications.iter()) {
+				let game_address = notification["game"].as_str().unwrap_or_default();
+				println!("Your proposal {} was accepted by {}, game {} started.",
+					notification["proposal"].as_str().unwrap_or_default(),
+					notification["accepted_by"].as_str().unwrap_or_default(),
+					game_address);
+				if current_game.is_none() {
+					println!("Setting current game hash to {}", game_address);
+					current_game = Some(game_address.into());
+				} else {
+					println!("Use \"join_game {}\" to play it.", game_address);
+				}
+			}
+		}
+
 		if let Some(current_game_string) = current_game.clone() {
- 			interface.set_prompt(&format!("{}> ", current_game_string))?;
+ 			let outcome = get_state(json!({"game_address": current_game_string.clone()}))
+ 				.map(|state| if state["outcome"] == "InProgress" {
+ 					format!("{}{}", clock_label(&state["clock"], &agent_addr), offer_label(&state, &agent_addr))
+ 				} else {
+ 					outcome_label(&state["outcome"], &agent_addr)
+ 				})
+ 				.unwrap_or_default();
+ 			interface.set_prompt(&format!("{}{}> ", current_game_string, outcome))?;
  			match render_game(json!({"game_address": current_game_string.clone()})) {
  				Ok(render_result) => {
-            		println!("{}", render_result.as_str().unwrap());
+            		println!("{}", render_result.as_str().unwrap_or_default());
  				},
- 				Err(_e) => {
- 					println!("No game is currently visible with that address.");
+ 				Err(e) => {
+ 					println!("No game is currently visible with that address: {}", e);
                     current_game = None;
  				}
  			}
@@ -250,23 +474,24 @@ fn holochain_call_generator(
 	};
 
 	Box::new(move |params| {
-		let call_result: serde_json::Value = client.post(url.clone())
+		let transport = |reason: String| GameError::Transport { reason }.to_string();
+
+		let response: serde_json::Value = client.post(url.clone())
 		    .json(&make_rpc_call(params))
-		    .send().map_err(|e| e.to_string())?
-		    .json()
-		    .map(|r: serde_json::Value| {
-		    	r["result"].clone()
-		    })
-		    .map(|s| serde_json::from_str(
-                s.as_str().expect(&format!("Holochain did not return a string result: {}", s))
-            ).expect(&format!("Holochain did not return a valid stringified JSON result: {}", s)))
-		    .map_err(|e| e.to_string())?;
+		    .send().map_err(|e| transport(e.to_string()))?
+		    .json().map_err(|e| transport(e.to_string()))?;
+
+		// the zome's result comes back as stringified JSON inside the RPC result
+		let call_result: serde_json::Value = response["result"].as_str()
+		    .ok_or_else(|| transport(format!("Holochain did not return a string result: {}", response)))
+		    .and_then(|s| serde_json::from_str(s)
+		        .map_err(|e| transport(format!("Holochain did not return a valid stringified JSON result: {}", e))))?;
 
 		// deal with the json encoded holochain error responses
 		if let Some(inner_result) = call_result.get("Ok") {
 			Ok(inner_result.clone())
 		} else {
-			Err(call_result["Err"].to_string())
+			Err(describe_error(&call_result["Err"]))
 		}
 	})
 
@@ -293,8 +518,197 @@ fn is_agent_addr(s: &str) -> bool {
 	s.starts_with("Hc") && s.len() == 63
 }
 
-fn current_timestamp() -> u32 {
-	SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as u32
+/// Renders a zome error, reading back the GameError the zome packed into it when there is one
+fn describe_error(error: &serde_json::Value) -> String {
+	if let Some(reason) = error.get("ValidationFailed").and_then(|reason| reason.as_str()) {
+		return GameError::rule_violation(reason).to_string();
+	}
+	error.get("Internal")
+		.and_then(|internal| internal.as_str())
+		.and_then(|internal| serde_json::from_str::<GameError>(internal).ok())
+		.map(|game_error| game_error.to_string())
+		.unwrap_or_else(|| error.to_string())
+}
+
+/// Shows how long each player has left right now, for use in the prompt of a timed game
+fn clock_label(clock: &serde_json::Value, agent_addr: &serde_json::Value) -> String {
+	match serde_json::from_value::<Clock>(clock.clone()) {
+		Ok(clock) => {
+			let now = current_timestamp();
+			let (mine, theirs) = if agent_addr == &clock.player_1.player {
+				(&clock.player_1, &clock.player_2)
+			} else {
+				(&clock.player_2, &clock.player_1)
+			};
+			format!(" [you {} | opponent {}]",
+				format_millis(clock.time_left(&mine.player, now)),
+				format_millis(clock.time_left(&theirs.player, now)))
+		}
+		Err(_) => String::new(),
+	}
+}
+
+/// Shows any draw offer or takeback request that is waiting for an answer, for use in the prompt
+fn offer_label(state: &serde_json::Value, agent_addr: &serde_json::Value) -> String {
+	let draw = match &state["draw_offered_by"] {
+		serde_json::Value::Null => "",
+		offered_by if offered_by == agent_addr => " (draw offered)",
+		_ => " (draw offered to you)",
+	};
+	let takeback = match &state["takeback_requested_by"] {
+		serde_json::Value::Null => "",
+		requested_by if requested_by == agent_addr => " (takeback requested)",
+		_ => " (takeback requested by opponent)",
+	};
+	format!("{}{}", draw, takeback)
+}
+
+/// Describes a finished game's outcome from the point of view of this agent, for use in the prompt
+fn outcome_label(outcome: &serde_json::Value, agent_addr: &serde_json::Value) -> String {
+	if outcome == "Drawn" {
+		" (drawn)".into()
+	} else if let Some(winner) = outcome.get("Won").or(outcome.get("Resigned")).or(outcome.get("TimedOut")).map(|o| &o["winner"]) {
+		if winner == agent_addr {
+			" (you won)".into()
+		} else {
+			" (you lost)".into()
+		}
+	} else {
+		String::new()
+	}
+}
+
+/// Describes a game's status from `list_my_games`
+fn status_label(status: &serde_json::Value) -> &'static str {
+	match status.as_str().unwrap_or_default() {
+		"MyTurn" => "your turn",
+		"TheirTurn" => "their turn",
+		"Won" => "you won",
+		"Lost" => "you lost",
+		"Drawn" => "drawn",
+		_ => "unknown",
+	}
+}
+
+/// Shows a game as a spectator sees it, redrawing it whenever a move is made, until the user
+/// presses a key
+fn watch_game(
+	get_state: &dyn Fn(serde_json::Value) -> Result<serde_json::Value, String>,
+	render_game: &dyn Fn(serde_json::Value) -> Result<serde_json::Value, String>,
+	game_address: &str,
+) -> Result<(), String> {
+	let terminal = DefaultTerminal::new().map_err(|e| e.to_string())?;
+	println!("Watching {}, press any key to stop.", game_address);
+	let mut last_state = serde_json::Value::Null;
+	loop {
+		// every move changes the state, even when a takeback leaves the number of moves the same
+		let state = get_state(json!({"game_address": game_address}))?;
+		if state != last_state {
+			let rendered = render_game(json!({"game_address": game_address, "spectator": true}))?;
+			println!("{}", rendered.as_str().unwrap_or_default());
+			last_state = state;
+		}
+		if key_pressed(&terminal, time::Duration::from_millis(2000)).map_err(|e| e.to_string())? {
+			return Ok(());
+		}
+	}
+}
+
+/// Waits up to `timeout` for any key, including ones like the arrow keys that a line editor would
+/// swallow, and consumes it
+fn key_pressed(terminal: &DefaultTerminal, timeout: time::Duration) -> io::Result<bool> {
+	let mut reader = terminal.lock_read();
+	// blocking signals means Ctrl-C is read as a key press rather than ending the CLI
+	let state = reader.prepare(true, SignalSet::new())?;
+	let mut pressed = false;
+	if reader.wait_for_input(Some(timeout))? {
+		pressed = match reader.read(&mut Vec::new())? {
+			RawRead::Bytes(n) => n > 0,
+			RawRead::Resize(_) | RawRead::Signal(_) => false,
+		};
+	}
+	reader.restore(state)?;
+	Ok(pressed)
+}
+
+/// Reads the options in front of a proposal's message, returning them as zome arguments
+fn proposal_options(args: &str, settings: serde_json::Value) -> Result<(serde_json::Value, &str), String> {
+	let mut options = json!({"settings": settings});
+	let mut rest = args;
+	loop {
+		let (option, after) = split_first_word(rest);
+		let (value, after_value) = split_first_word(after);
+		match option {
+			"--for" if is_agent_addr(value) => options["invited"] = json!(value),
+			"--for" => return Err("--for must be followed by the agent address of the player to invite".into()),
+			"--expires" => {
+				let minutes: u64 = value.parse().map_err(|_| "--expires must be followed by a number of minutes")?;
+				let expires_at = minutes.checked_mul(60 * 1000)
+					.and_then(|millis| current_timestamp().checked_add(millis))
+					.ok_or("--expires is too far in the future")?;
+				options["expires_at"] = json!(expires_at);
+			},
+			"--max" => {
+				let max: u32 = value.parse().map_err(|_| "--max must be followed by a number of games")?;
+				options["max_acceptances"] = json!(max);
+			},
+			"--you-first" => {
+				options["settings"]["acceptor_moves_first"] = json!(true);
+				rest = after;
+				continue;
+			},
+			_ => return Ok((options, rest)),
+		}
+		rest = after_value;
+	}
+}
+
+/// Describes the game a proposal offers and who can still accept it
+fn describe_proposal(proposal: &serde_json::Value, agent_addr: &serde_json::Value) -> String {
+	let settings = &proposal["settings"];
+	let mut description = format!("{} game", settings["ruleset"].as_str().unwrap_or("TicTacToe"));
+	if settings["acceptor_moves_first"] == true {
+		description.push_str(", you move first");
+	}
+	if !proposal["invited"].is_null() {
+		description.push_str(if &proposal["invited"] == agent_addr { ", for you" } else { ", for another agent" });
+	}
+	if let Some(max) = proposal["max_acceptances"].as_u64() {
+		description.push_str(&format!(", at most {} game{}", max, if max == 1 { "" } else { "s" }));
+	}
+	if let Some(expires_at) = proposal["expires_at"].as_u64() {
+		let now = current_timestamp();
+		if expires_at < now {
+			description.push_str(", expired");
+		} else {
+			description.push_str(&format!(", expires in {}", format_millis(expires_at.saturating_sub(now))));
+		}
+	}
+	description
+}
+
+/// Summarises a player's record from `get_player_stats`
+fn describe_stats(stats: &serde_json::Value) -> String {
+	let count = |field: &str| stats[field].as_u64().unwrap_or_default();
+	format!("{} won, {} lost ({} resigned), {} drawn",
+		count("wins"), count("losses"), count("resignations"), count("draws"))
+}
+
+/// How long ago a timestamp was, roughly, e.g. "3 hours ago"
+fn describe_age(timestamp: u64) -> String {
+	let seconds = current_timestamp().saturating_sub(timestamp) / 1000;
+	let (amount, unit) = match seconds {
+		0..=59 => return "just now".into(),
+		60..=3599 => (seconds / 60, "minute"),
+		3600..=86399 => (seconds / 3600, "hour"),
+		_ => (seconds / 86400, "day"),
+	};
+	format!("{} {}{} ago", amount, unit, if amount == 1 { "" } else { "s" })
+}
+
+/// Milliseconds since the Unix epoch
+fn current_timestamp() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
 }
 
 /*=====  End of Helpers  ======*/
@@ -0,0 +1,204 @@
+use std::str::FromStr;
+
+use crate::Game;
+
+/**
+ *
+ * Optional time controls. A game either gives each move a fixed amount of time, or gives each
+ * player a total budget that grows by an increment after each of their moves (Fischer).
+ * Time controls are given in seconds, clocks run in milliseconds measured with the timestamps
+ * the players put on their moves.
+ *
+ */
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TimeControl {
+    PerMove { seconds: u32 },
+    Fischer { initial: u32, increment: u32 },
+}
+
+/// Reads `30` as 30 seconds per move and `300+5` as five minutes each plus 5 seconds per move
+impl FromStr for TimeControl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let seconds = |n: &str| {
+            n.trim()
+                .parse::<u32>()
+                .map_err(|_| format!("{} is not a number of seconds", n))
+        };
+        match s.find('+') {
+            Some(pos) => Ok(TimeControl::Fischer {
+                initial: seconds(&s[..pos])?,
+                increment: seconds(&s[pos + 1..])?,
+            }),
+            None => Ok(TimeControl::PerMove {
+                seconds: seconds(s)?,
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PlayerClock {
+    pub player: String,
+    /// Milliseconds the player had left when their current or next turn started
+    pub remaining: u64,
+}
+
+/// Both players' clocks, and which of them is running
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Clock {
+    pub time_control: TimeControl,
+    pub player_1: PlayerClock,
+    pub player_2: PlayerClock,
+    pub running: String,
+    pub turn_started_at: u64,
+}
+
+impl Clock {
+    /// The clocks at the start of a game, or None if it is not timed
+    pub fn start(game: &Game) -> Option<Clock> {
+        game.time_control.map(|time_control| {
+            let remaining = match time_control {
+                TimeControl::PerMove { seconds } => millis(seconds),
+                TimeControl::Fischer { initial, .. } => millis(initial),
+            };
+            Clock {
+                time_control,
+                player_1: PlayerClock {
+                    player: game.player_1.clone(),
+                    remaining,
+                },
+                player_2: PlayerClock {
+                    player: game.player_2.clone(),
+                    remaining,
+                },
+                running: game.first_player().to_string(),
+                turn_started_at: game.created_at,
+            }
+        })
+    }
+
+    fn player_clock(&self, player: &str) -> &PlayerClock {
+        if self.player_1.player == player {
+            &self.player_1
+        } else {
+            &self.player_2
+        }
+    }
+
+    /// How long `player` has left at time `now`
+    pub fn time_left(&self, player: &str, now: u64) -> u64 {
+        let remaining = self.player_clock(player).remaining;
+        if self.running == player {
+            remaining.saturating_sub(now.saturating_sub(self.turn_started_at))
+        } else {
+            remaining
+        }
+    }
+
+    /// Whether `player` ran out of time before `now`
+    pub fn has_expired(&self, player: &str, now: u64) -> bool {
+        self.running == player
+            && now.saturating_sub(self.turn_started_at) > self.player_clock(player).remaining
+    }
+
+    /// The clocks after `mover` finishes their turn at `timestamp`, starting their opponent's
+    pub fn after_move(&self, game: &Game, mover: &str, timestamp: u64) -> Clock {
+        let mut next = self.clone();
+        let used = timestamp.saturating_sub(self.turn_started_at);
+        let remaining = match self.time_control {
+            TimeControl::PerMove { seconds } => millis(seconds),
+            TimeControl::Fischer { increment, .. } => self
+                .player_clock(mover)
+                .remaining
+                .saturating_sub(used)
+                .saturating_add(millis(increment)),
+        };
+        if next.player_1.player == mover {
+            next.player_1.remaining = remaining;
+        } else {
+            next.player_2.remaining = remaining;
+        }
+        next.running = game.opponent(mover).to_string();
+        next.turn_started_at = timestamp;
+        next
+    }
+
+    /// The clocks after the turn goes back to `player` at `timestamp` without a move being made,
+    /// as when a move is taken back. Whoever's clock was running keeps the time they used.
+    pub fn hand_back(&self, player: &str, timestamp: u64) -> Clock {
+        let mut next = self.clone();
+        if let TimeControl::Fischer { .. } = self.time_control {
+            let remaining = self.time_left(&self.running, timestamp);
+            if next.player_1.player == self.running {
+                next.player_1.remaining = remaining;
+            } else {
+                next.player_2.remaining = remaining;
+            }
+        }
+        next.running = player.to_string();
+        next.turn_started_at = timestamp;
+        next
+    }
+
+    /// A move can only be made while the mover still has time on their clock
+    pub fn is_in_time(&self, mover: &str, timestamp: u64) -> Result<(), String> {
+        if self.has_expired(mover, timestamp) {
+            Err("This player has run out of time, their opponent can claim the game".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// A timeout can only be claimed by the waiting player once the other player's time is up
+    pub fn is_valid_claim(&self, claimant: &str, timestamp: u64) -> Result<(), String> {
+        if self.running == claimant {
+            Err("A player cannot claim a timeout on their own turn".into())
+        } else if !self.has_expired(&self.running, timestamp) {
+            Err("The opponent still has time left".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Describes both clocks as they stood when the current turn started
+    pub fn describe(&self, viewer: Option<&str>) -> String {
+        let label = |clock: &PlayerClock| match viewer {
+            Some(viewer) if viewer == clock.player => "You".to_string(),
+            _ => clock.player.clone(),
+        };
+        let line = |clock: &PlayerClock| {
+            format!(
+                "{}: {}{}",
+                label(clock),
+                format_millis(clock.remaining),
+                if self.running == clock.player {
+                    " (running)"
+                } else {
+                    ""
+                }
+            )
+        };
+        format!("{}\n{}", line(&self.player_1), line(&self.player_2))
+    }
+}
+
+fn millis(seconds: u32) -> u64 {
+    u64::from(seconds) * 1000
+}
+
+/// Formats a duration in milliseconds as m:ss, or h:mm:ss for an hour or more
+pub fn format_millis(millis: u64) -> String {
+    let seconds = millis / 1000;
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
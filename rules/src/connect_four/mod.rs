@@ -0,0 +1,59 @@
+pub mod moves;
+/**
+ * Connect Four, a second game implementing the same framework as tic-tac-toe.
+ * Pieces are dropped into columns and fall to the bottom of the board.
+ */
+pub mod state;
+pub mod validation;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::{moves::MoveType, state::GameState};
+use crate::{Game, GameRules, Outcome, PlayerMove};
+
+pub type Move = PlayerMove<MoveType>;
+
+/// Connect Four played within the generic game framework
+pub struct ConnectFour;
+
+impl GameRules for ConnectFour {
+    type State = GameState;
+    type Move = MoveType;
+
+    const DEFAULT_WIDTH: usize = state::DEFAULT_WIDTH;
+    const DEFAULT_HEIGHT: usize = state::DEFAULT_HEIGHT;
+    const DEFAULT_WIN_LENGTH: usize = state::DEFAULT_WIN_LENGTH;
+
+    fn initial(game: &Game) -> GameState {
+        GameState::initial(game)
+    }
+
+    fn evolve(game: &Game, state: &GameState, next_move: &Move) -> GameState {
+        state.evolve(game, next_move)
+    }
+
+    fn is_valid(game: &Game, state: &GameState, next_move: &Move) -> Result<(), String> {
+        next_move.is_valid(game, state)
+    }
+
+    fn is_valid_game(game: &Game) -> Result<(), String> {
+        validation::is_valid_board(game)
+    }
+
+    fn render(game: &Game, state: &GameState, viewer: Option<&str>) -> String {
+        state.render(game, viewer)
+    }
+
+    fn describe_moves() -> Vec<MoveType> {
+        MoveType::describe()
+    }
+
+    fn outcome(state: &GameState) -> &Outcome {
+        &state.outcome
+    }
+
+    fn next_player<'a>(game: &'a Game, state: &GameState) -> &'a str {
+        state.next_player(game)
+    }
+}
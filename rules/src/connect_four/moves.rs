@@ -0,0 +1,72 @@
+use super::state::landing_row;
+use super::GameState;
+use crate::placement::{Board, MoveKind, Piece, Placement};
+use crate::Game;
+
+/**
+ *
+ * In Connect Four the only way to place a piece is to drop it into a column,
+ * where it falls to the lowest empty row.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum MoveType {
+    Drop { column: usize },
+    Resign,
+    ClaimTimeout,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    RequestTakeback,
+    AcceptTakeback,
+}
+
+impl MoveType {
+    pub fn describe() -> Vec<MoveType> {
+        vec![
+            MoveType::Drop { column: 0 },
+            MoveType::Resign,
+            MoveType::ClaimTimeout,
+            MoveType::OfferDraw,
+            MoveType::AcceptDraw,
+            MoveType::DeclineDraw,
+            MoveType::RequestTakeback,
+            MoveType::AcceptTakeback,
+        ]
+    }
+}
+
+/// A drop is valid when the column exists and still has room
+impl Placement for MoveType {
+    fn kind(&self) -> MoveKind {
+        match self {
+            MoveType::Drop { .. } => MoveKind::Place,
+            MoveType::Resign => MoveKind::Resign,
+            MoveType::ClaimTimeout => MoveKind::ClaimTimeout,
+            MoveType::OfferDraw => MoveKind::OfferDraw,
+            MoveType::AcceptDraw => MoveKind::AcceptDraw,
+            MoveType::DeclineDraw => MoveKind::DeclineDraw,
+            MoveType::RequestTakeback => MoveKind::RequestTakeback,
+            MoveType::AcceptTakeback => MoveKind::AcceptTakeback,
+        }
+    }
+
+    fn place(&self, game: &Game, state: &GameState) -> Result<Piece, String> {
+        match *self {
+            MoveType::Drop { column } => {
+                if column >= game.width {
+                    return Err("Column is not on the board".into());
+                }
+                landing_row(game, state, column)
+                    .map(|row| Piece { x: column, y: row })
+                    .ok_or_else(|| "Column is full".into())
+            }
+            _ => Err("Only dropping a piece puts it on the board".into()),
+        }
+    }
+
+    fn render_board(board: &Board) -> String {
+        super::state::render_board(board)
+    }
+}
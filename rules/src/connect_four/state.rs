@@ -0,0 +1,58 @@
+use super::MoveType;
+use crate::placement::{self, Board};
+use crate::Game;
+
+pub use crate::placement::Piece;
+
+/**
+ *
+ * The state keeps every move along with where each player's pieces came to rest, as in every
+ * placement game. Rows are counted from the top of the board, so pieces fall towards the last row.
+ *
+ */
+pub type GameState = placement::GameState<MoveType>;
+
+/// The standard Connect Four board is 7 columns by 6 rows with four in a row to win
+pub const DEFAULT_WIDTH: usize = 7;
+pub const DEFAULT_HEIGHT: usize = 6;
+pub const DEFAULT_WIN_LENGTH: usize = 4;
+
+/// The row a piece dropped into `column` comes to rest in, or None if the column is full
+pub fn landing_row(game: &Game, state: &GameState, column: usize) -> Option<usize> {
+    let filled = state
+        .player_1_pieces
+        .iter()
+        .chain(state.player_2_pieces.iter())
+        .filter(|piece| piece.x == column)
+        .count();
+    game.height.checked_sub(filled + 1)
+}
+
+/// Draws the pieces on the board, see `Placement::render_board`
+pub(super) fn render_board(board: &Board) -> String {
+    let width = board.0.first().map(|row| row.len()).unwrap_or(0);
+    // every cell is as wide as the widest column label
+    let cell_width = width.saturating_sub(1).to_string().len();
+    let mut lines = String::new();
+    for row in board.0.iter() {
+        for &cell in row.iter() {
+            lines.push_str(&format!(
+                "|{:>w$}",
+                match cell {
+                    2 => 'X',
+                    1 => 'O',
+                    _ => ' ',
+                },
+                w = cell_width
+            ));
+        }
+        lines.push_str("|\n");
+    }
+    lines.push_str(&format!("+{}\n", format!("{}+", "-".repeat(cell_width)).repeat(width)));
+    // label the columns underneath, since that is where pieces are dropped
+    for x in 0..width {
+        lines.push_str(&format!(" {:>w$}", x, w = cell_width));
+    }
+    lines.push('\n');
+    lines
+}
@@ -0,0 +1,188 @@
+use super::state::Piece;
+use super::{GameState, Move, MoveType};
+use crate::{Game, Outcome};
+
+fn new_game() -> Game {
+    Game {
+        player_1: "player_1".into(),
+        player_2: "player_2".into(),
+        created_at: 0,
+        width: 7,
+        height: 6,
+        win_length: 4,
+        time_control: None,
+        player_1_moves_first: false,
+    }
+}
+
+/// Drops pieces into `columns` in turn, player 2 first, checking every move is valid
+fn play(game: &Game, columns: &[usize]) -> GameState {
+    columns.iter().fold(GameState::initial(game), |state, &column| {
+        let next_move = Move {
+            author: state.next_player(game).into(),
+            move_type: MoveType::Drop { column },
+            timestamp: 0,
+        };
+        assert_eq!(next_move.is_valid(game, &state), Ok(()));
+        state.evolve(game, &next_move)
+    })
+}
+
+#[test]
+fn pieces_fall_to_the_lowest_empty_row() {
+    let game = new_game();
+    let state = play(&game, &[3, 3]);
+    assert_eq!(state.player_2_pieces, vec![Piece { x: 3, y: 5 }]);
+    assert_eq!(state.player_1_pieces, vec![Piece { x: 3, y: 4 }]);
+}
+
+#[test]
+fn full_columns_reject_drops() {
+    let game = new_game();
+    let state = play(&game, &[0, 0, 0, 0, 0, 0]);
+    let next_move = Move {
+        author: state.next_player(&game).into(),
+        move_type: MoveType::Drop { column: 0 },
+        timestamp: 0,
+    };
+    assert_eq!(next_move.is_valid(&game, &state), Err("Column is full".into()));
+}
+
+#[test]
+fn four_in_a_column_wins() {
+    let game = new_game();
+    let state = play(&game, &[0, 1, 0, 1, 0, 1, 0]);
+    assert_eq!(
+        state.outcome,
+        Outcome::Won {
+            winner: game.player_2.clone()
+        }
+    );
+}
+
+#[test]
+fn four_on_a_diagonal_wins() {
+    let game = new_game();
+    // player 2 builds a rising diagonal from (0, 5) to (3, 2)
+    let state = play(&game, &[0, 1, 1, 2, 2, 3, 2, 3, 3, 6, 3]);
+    assert_eq!(
+        state.outcome,
+        Outcome::Won {
+            winner: game.player_2.clone()
+        }
+    );
+}
+
+#[test]
+fn a_full_board_with_no_line_is_a_draw() {
+    let game = Game {
+        width: 2,
+        height: 2,
+        win_length: 3,
+        ..new_game()
+    };
+    let state = play(&game, &[0, 0, 1, 1]);
+    assert_eq!(state.outcome, Outcome::Drawn);
+}
+
+fn new_move(author: &str, move_type: MoveType) -> Move {
+    Move {
+        author: author.into(),
+        move_type,
+        timestamp: 0,
+    }
+}
+
+#[test]
+fn players_take_turns_starting_with_player_2() {
+    let game = new_game();
+    let state = GameState::initial(&game);
+    assert_eq!(
+        new_move("player_1", MoveType::Drop { column: 0 }).is_valid(&game, &state),
+        Err("The other player must make the first move".into())
+    );
+    let state = play(&game, &[0]);
+    assert_eq!(
+        new_move("player_2", MoveType::Drop { column: 1 }).is_valid(&game, &state),
+        Err("It is not this player's turn".into())
+    );
+    // offers are not turns, so they can be made while waiting
+    assert_eq!(
+        new_move("player_2", MoveType::OfferDraw).is_valid(&game, &state),
+        Ok(())
+    );
+}
+
+#[test]
+fn draw_offers_are_accepted_or_declined_by_playing_on() {
+    let game = new_game();
+    let offer = new_move("player_2", MoveType::OfferDraw);
+    let state = play(&game, &[3]).evolve(&game, &offer);
+    assert_eq!(state.draw_offered_by, Some("player_2".into()));
+    assert!(new_move("player_2", MoveType::AcceptDraw)
+        .is_valid(&game, &state)
+        .is_err());
+
+    let declined = state.evolve(&game, &new_move("player_1", MoveType::Drop { column: 3 }));
+    assert_eq!(declined.draw_offered_by, None);
+    assert_eq!(declined.outcome, Outcome::InProgress);
+
+    let accept = new_move("player_1", MoveType::AcceptDraw);
+    assert_eq!(accept.is_valid(&game, &state), Ok(()));
+    let drawn = state.evolve(&game, &accept);
+    assert_eq!(drawn.outcome, Outcome::Drawn);
+    assert_eq!(
+        new_move("player_1", MoveType::Drop { column: 0 }).is_valid(&game, &drawn),
+        Err("This game has ended in a draw".into())
+    );
+}
+
+#[test]
+fn resigning_ends_the_game_on_the_players_turn() {
+    let game = new_game();
+    let state = play(&game, &[3]);
+    let resign = new_move("player_1", MoveType::Resign);
+    assert_eq!(resign.is_valid(&game, &state), Ok(()));
+    assert_eq!(
+        state.evolve(&game, &resign).outcome,
+        Outcome::Resigned {
+            winner: game.player_2.clone()
+        }
+    );
+}
+
+#[test]
+fn takebacks_lift_the_requesters_last_piece_out_of_its_column() {
+    let game = new_game();
+    let state = play(&game, &[3, 3]);
+    assert!(new_move("player_2", MoveType::RequestTakeback)
+        .is_valid(&game, &state)
+        .is_err());
+
+    let request = new_move("player_1", MoveType::RequestTakeback);
+    assert_eq!(request.is_valid(&game, &state), Ok(()));
+    let state = state.evolve(&game, &request);
+    let accept = new_move("player_2", MoveType::AcceptTakeback);
+    assert_eq!(accept.is_valid(&game, &state), Ok(()));
+    let state = state.evolve(&game, &accept);
+
+    assert_eq!(state.takeback_requested_by, None);
+    assert!(state.player_1_pieces.is_empty());
+    assert_eq!(state.next_player(&game), "player_1");
+    // the column has room for the piece again and it lands where it did before
+    let state = state.evolve(&game, &new_move("player_1", MoveType::Drop { column: 3 }));
+    assert_eq!(state.player_1_pieces, vec![Piece { x: 3, y: 4 }]);
+}
+
+#[test]
+fn pending_offers_are_shown_to_both_players() {
+    let game = new_game();
+    let request = new_move("player_2", MoveType::RequestTakeback);
+    let state = play(&game, &[3]).evolve(&game, &request);
+    assert!(state
+        .render(&game, Some("player_1"))
+        .contains("Your opponent has proposed a takeback"));
+    assert!(state
+        .render(&game, Some("player_2"))
+        .contains("You have proposed a takeback, waiting for your opponent"));
+}
@@ -0,0 +1,6 @@
+use crate::Game;
+
+/// Connect Four boards have the same limits as any tic-tac-toe board
+pub fn is_valid_board(game: &Game) -> Result<(), String> {
+    crate::tic_tac_toe::validation::is_valid_board(game)
+}
@@ -0,0 +1,53 @@
+use std::fmt;
+
+/**
+ *
+ * Everything that can go wrong loading, validating or talking to a game.
+ * The zome returns these (as JSON inside its `Internal` errors) and the CLI reads them back,
+ * so both ends agree on what happened rather than passing around free text.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum GameError {
+    /// There is nothing stored at this address, or it could not be fetched
+    NotFound { address: String },
+    /// Something is stored at this address but it is not what was asked for
+    WrongEntryType { address: String, expected: String },
+    /// The moves of a game cannot be put back together into a history
+    CorruptMoveChain { reason: String },
+    /// A move or game breaks the rules, the reason says which rule
+    RuleViolation { reason: String },
+    /// The call never got a usable answer from the conductor
+    Transport { reason: String },
+}
+
+impl GameError {
+    pub fn rule_violation<S: Into<String>>(reason: S) -> Self {
+        GameError::RuleViolation {
+            reason: reason.into(),
+        }
+    }
+
+    /// Asking for a move on the opponent's turn, which a client can wait out
+    pub fn not_your_turn() -> Self {
+        GameError::rule_violation("It is not your turn")
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::NotFound { address } => write!(f, "Nothing could be found at {}", address),
+            GameError::WrongEntryType { address, expected } => {
+                write!(f, "The entry at {} is not a {}", address, expected)
+            }
+            GameError::CorruptMoveChain { reason } => {
+                write!(f, "The moves of this game are corrupt: {}", reason)
+            }
+            GameError::RuleViolation { reason } => write!(f, "{}", reason),
+            GameError::Transport { reason } => {
+                write!(f, "Could not talk to the conductor: {}", reason)
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+/**
+ * The rules of the games, kept free of any Holochain dependencies.
+ *
+ * The zome links this crate to validate and evolve games on the DHT, and the CLI links it directly
+ * to play offline, so both always agree on exactly the same rules.
+ */
+#[macro_use]
+extern crate serde_derive;
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+pub mod clock;
+pub mod connect_four;
+mod error;
+pub mod offers;
+pub mod placement;
+pub mod rating;
+pub mod tic_tac_toe;
+
+pub use clock::{Clock, TimeControl};
+pub use error::GameError;
+
+/**
+ *
+ * The generic game framework. Everything a game needs to be played, validated and displayed is
+ * described by implementing `GameRules`. The zome and the CLI are written against this trait only,
+ * so adding a new game means adding a new implementation here and nothing else.
+ *
+ */
+pub trait GameRules {
+    /// The state of a game after some number of moves have been made
+    type State: Clone + Debug + Serialize + DeserializeOwned;
+    /// The types of moves that can be made in this game and the data they carry
+    type Move: Clone + Debug + Serialize + DeserializeOwned + PartialEq;
+
+    /// The board a game is played on when its creator does not choose one
+    const DEFAULT_WIDTH: usize;
+    const DEFAULT_HEIGHT: usize;
+    const DEFAULT_WIN_LENGTH: usize;
+
+    /// The state of a game before any moves have been made
+    fn initial(game: &Game) -> Self::State;
+
+    /// Computes the state after a move. The move can be assumed to be valid.
+    fn evolve(game: &Game, state: &Self::State, next_move: &PlayerMove<Self::Move>) -> Self::State;
+
+    /// Decides if a move is allowed. The error should tell the player why it is not.
+    fn is_valid(
+        game: &Game,
+        state: &Self::State,
+        next_move: &PlayerMove<Self::Move>,
+    ) -> Result<(), String>;
+
+    /// Decides if a game can be played with the settings it was created with
+    fn is_valid_game(game: &Game) -> Result<(), String>;
+
+    /// Renders the state for `viewer`, or for a spectator if they are not one of the players
+    fn render(game: &Game, state: &Self::State, viewer: Option<&str>) -> String;
+
+    /// An example of every type of move, to show players what they can do
+    fn describe_moves() -> Vec<Self::Move>;
+
+    /// Whether the game is still being played and if not how it ended
+    fn outcome(state: &Self::State) -> &Outcome;
+
+    /// The player who must make the next move
+    fn next_player<'a>(game: &'a Game, state: &Self::State) -> &'a str;
+
+    /// Suggests a move for the next player, if this game has a computer player
+    fn best_move(_game: &Game, _state: &Self::State) -> Option<Self::Move> {
+        None
+    }
+}
+
+/**
+ *
+ * Every game that can be played. Games record their ruleset so that anyone loading one later
+ * knows which `GameRules` to validate and render it with, see `with_rules!`.
+ *
+ */
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Ruleset {
+    TicTacToe,
+    ConnectFour,
+}
+
+// `#[default]` variants are newer than the toolchain the zome is built with
+#[allow(clippy::derivable_impls)]
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset::TicTacToe
+    }
+}
+
+impl FromStr for Ruleset {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "tic_tac_toe" => Ok(Ruleset::TicTacToe),
+            "connect_four" => Ok(Ruleset::ConnectFour),
+            _ => Err(format!(
+                "Unknown game {}, expected tic_tac_toe or connect_four",
+                name
+            )),
+        }
+    }
+}
+
+/// Evaluates `$body` with the type `$rules` standing for the `GameRules` of `$ruleset`
+#[macro_export]
+macro_rules! with_rules {
+    ($ruleset:expr, $rules:ident => $body:expr) => {
+        match $ruleset {
+            $crate::Ruleset::TicTacToe => {
+                type $rules = $crate::tic_tac_toe::TicTacToe;
+                $body
+            }
+            $crate::Ruleset::ConnectFour => {
+                type $rules = $crate::connect_four::ConnectFour;
+                $body
+            }
+        }
+    };
+}
+
+/**
+ *
+ * The players and board of a game, and a move made by one of those players.
+ * These carry just what the rules need to know, the zome keeps the rest (hashes, links)
+ * on its own entries and converts them when it needs to evolve or validate a game.
+ *
+ * Players are identified by any string that is unique to them, e.g. their agent address.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Game {
+    pub player_1: String,
+    pub player_2: String,
+    /// Milliseconds since the Unix epoch, as are all timestamps
+    pub created_at: u64,
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,
+    pub time_control: Option<TimeControl>,
+    /// Player 2 makes the first move unless this is set
+    #[serde(default)]
+    pub player_1_moves_first: bool,
+}
+
+impl Game {
+    /// The player who makes the first move
+    pub fn first_player(&self) -> &str {
+        if self.player_1_moves_first {
+            &self.player_1
+        } else {
+            &self.player_2
+        }
+    }
+
+    pub fn is_player(&self, player: &str) -> bool {
+        player == self.player_1 || player == self.player_2
+    }
+
+    /// The other player in this game
+    pub fn opponent(&self, player: &str) -> &str {
+        if player == self.player_1 {
+            &self.player_2
+        } else {
+            &self.player_1
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PlayerMove<M> {
+    pub author: String,
+    pub move_type: M,
+    pub timestamp: u64,
+}
+
+/**
+ *
+ * How a game stands after the moves made so far. Once a game is no longer `InProgress`
+ * no further moves are accepted.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Outcome {
+    InProgress,
+    Won { winner: String },
+    Drawn,
+    Resigned { winner: String },
+    TimedOut { winner: String },
+}
+
+impl Outcome {
+    pub fn is_over(&self) -> bool {
+        *self != Outcome::InProgress
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        match self {
+            Outcome::Won { winner } | Outcome::Resigned { winner } | Outcome::TimedOut { winner } => {
+                Some(winner.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// Describes the outcome, or whose turn it is, from the point of view of `viewer`
+    pub fn describe(&self, game: &Game, next_player: &str, viewer: Option<&str>) -> String {
+        let is_viewer = |player: &str| viewer == Some(player);
+        let viewer_is_player = is_viewer(&game.player_1) || is_viewer(&game.player_2);
+        match self {
+            Outcome::Won { winner } if is_viewer(winner) => "You have won this game".into(),
+            Outcome::Won { .. } if viewer_is_player => "Your opponent has won this game".into(),
+            Outcome::Won { winner } => format!("{} has won this game", winner),
+            Outcome::Resigned { winner } if is_viewer(winner) => {
+                "Your opponent resigned, you have won this game".into()
+            }
+            Outcome::Resigned { .. } if viewer_is_player => {
+                "You resigned, your opponent has won this game".into()
+            }
+            Outcome::Resigned { winner } => format!(
+                "{} resigned, {} has won this game",
+                game.opponent(winner),
+                winner
+            ),
+            Outcome::TimedOut { winner } if is_viewer(winner) => {
+                "Your opponent ran out of time, you have won this game".into()
+            }
+            Outcome::TimedOut { .. } if viewer_is_player => {
+                "You ran out of time, your opponent has won this game".into()
+            }
+            Outcome::TimedOut { winner } => format!(
+                "{} ran out of time, {} has won this game",
+                game.opponent(winner),
+                winner
+            ),
+            Outcome::Drawn => "This game ended in a draw".into(),
+            Outcome::InProgress if is_viewer(next_player) => "It is your turn".into(),
+            Outcome::InProgress if viewer_is_player => "It is your opponent's turn".into(),
+            Outcome::InProgress => format!("It is {}'s turn", next_player),
+        }
+    }
+}
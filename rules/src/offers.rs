@@ -0,0 +1,39 @@
+use crate::Game;
+
+/**
+ *
+ * Offers that one player makes and only their opponent can answer, such as a draw or a takeback.
+ * A pending offer is kept in the game state as the player who made it, and only one offer of
+ * each kind can be waiting for an answer at a time.
+ *
+ */
+pub fn can_offer(pending: &Option<String>, what: &str) -> Result<(), String> {
+    match pending {
+        Some(_) => Err(format!("A {} has already been proposed", what)),
+        None => Ok(()),
+    }
+}
+
+/// An offer can only be accepted or declined by the opponent of the player who made it
+pub fn can_answer(pending: &Option<String>, player: &str, what: &str) -> Result<(), String> {
+    match pending {
+        None => Err(format!("No {} has been proposed", what)),
+        Some(offered_by) if offered_by == player => {
+            Err(format!("Only the opponent can answer a {} proposal", what))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// Describes a pending offer from the point of view of `viewer`
+pub fn describe(game: &Game, offered_by: &str, viewer: Option<&str>, what: &str) -> String {
+    match viewer {
+        Some(viewer) if viewer == offered_by => {
+            format!("You have proposed a {}, waiting for your opponent", what)
+        }
+        Some(viewer) if viewer == game.opponent(offered_by) => {
+            format!("Your opponent has proposed a {}", what)
+        }
+        _ => format!("{} has proposed a {}", offered_by, what),
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{offers, Clock, Game, Outcome, PlayerMove};
+
+/**
+ *
+ * The framework shared by games where players take turns placing pieces on a grid, such as
+ * tic-tac-toe and Connect Four. Turns, resigning, timeouts, draw offers and takebacks work the
+ * same way in all of them, so a ruleset only says which kind each of its moves is and where a
+ * placement puts its piece, by implementing `Placement` for its moves.
+ *
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveKind {
+    Place,
+    Resign,
+    ClaimTimeout,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    RequestTakeback,
+    AcceptTakeback,
+}
+
+impl MoveKind {
+    /// Whether this move uses up the player's turn. Offers and claims can be made at any time.
+    // `matches!` is newer than the toolchain the zome is built with
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn takes_turn(self) -> bool {
+        match self {
+            MoveKind::Place | MoveKind::Resign => true,
+            _ => false,
+        }
+    }
+}
+
+/// The moves of a game played on a grid, see `MoveKind` for the ones every such game has
+pub trait Placement: Clone + Sized {
+    fn kind(&self) -> MoveKind;
+
+    /// Where a move of kind `Place` puts its piece, or why it cannot be placed
+    fn place(&self, game: &Game, state: &GameState<Self>) -> Result<Piece, String>;
+
+    /// Draws just the board, `GameState::render` adds whose turn it is and any pending offers
+    fn render_board(board: &Board) -> String;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameState<M> {
+    /// Every move made, except for moves that were taken back
+    pub moves: Vec<PlayerMove<M>>,
+    pub player_1_pieces: Vec<Piece>,
+    pub player_2_pieces: Vec<Piece>,
+    pub outcome: Outcome,
+    pub clock: Option<Clock>,
+    /// The player who offered a draw, while their opponent has yet to answer
+    pub draw_offered_by: Option<String>,
+    /// The player who asked to take back their last move, while their opponent has yet to answer
+    pub takeback_requested_by: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Piece {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Piece {
+    pub fn is_in_bounds(&self, game: &Game) -> Result<(), String> {
+        if self.x < game.width && self.y < game.height {
+            Ok(())
+        } else {
+            Err("Piece is not in bounds".into())
+        }
+    }
+
+    pub fn is_empty<M>(&self, game_state: &GameState<M>) -> Result<(), String> {
+        if game_state.player_1_pieces.contains(self) || game_state.player_2_pieces.contains(self) {
+            Err("Location is not empty".into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<M: Placement> GameState<M> {
+    pub fn initial(game: &Game) -> Self {
+        Self {
+            moves: Vec::new(),
+            player_1_pieces: Vec::new(),
+            player_2_pieces: Vec::new(),
+            outcome: Outcome::InProgress,
+            clock: Clock::start(game),
+            draw_offered_by: None,
+            takeback_requested_by: None,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_over()
+    }
+
+    /// The player whose turn it is
+    pub fn next_player<'a>(&self, game: &'a Game) -> &'a str {
+        match self.last_turn() {
+            Some(last_move) => game.opponent(&last_move.author),
+            None => game.first_player(),
+        }
+    }
+
+    /// The most recent move that used up a player's turn
+    pub fn last_turn(&self) -> Option<&PlayerMove<M>> {
+        self.moves
+            .iter()
+            .rev()
+            .find(|m| m.move_type.kind().takes_turn())
+    }
+
+    /// Undoes `player`'s most recent move and hands the turn back to them
+    fn take_back(&mut self, game: &Game, player: &str, timestamp: u64) {
+        if let Some(index) = self
+            .moves
+            .iter()
+            .rposition(|m| m.move_type.kind().takes_turn())
+        {
+            self.moves.remove(index);
+        }
+        if game.player_1 == player {
+            self.player_1_pieces.pop();
+        } else {
+            self.player_2_pieces.pop();
+        }
+        self.clock = self
+            .clock
+            .as_ref()
+            .map(|clock| clock.hand_back(player, timestamp));
+    }
+
+    /// Renders the board for `viewer`, or for a spectator if they are not one of the players
+    pub fn render(&self, game: &Game, viewer: Option<&str>) -> String {
+        let mut turn = self.outcome.describe(game, self.next_player(game), viewer);
+        let pending = [
+            (&self.draw_offered_by, "draw"),
+            (&self.takeback_requested_by, "takeback"),
+        ];
+        for (offered_by, what) in pending.iter().filter(|_| !self.is_over()) {
+            if let Some(offered_by) = offered_by {
+                turn.push('\n');
+                turn.push_str(&offers::describe(game, offered_by, viewer, what));
+            }
+        }
+        let board = M::render_board(&Board::set(game, self));
+        match &self.clock {
+            Some(clock) if !self.is_over() => {
+                format!("{}\n{}\n\n{}", turn, clock.describe(viewer), board)
+            }
+            _ => format!("{}\n\n{}", turn, board),
+        }
+    }
+
+    pub fn evolve(&self, game: &Game, next_move: &PlayerMove<M>) -> Self {
+        // given a current state, a game and a move, compute the next state
+        // You can assume all moves are valid
+
+        let mut next_state = self.clone();
+        next_state.moves.push(next_move.clone());
+
+        let outcome = match next_move.move_type.kind() {
+            MoveKind::Place => match next_move.move_type.place(game, self) {
+                Ok(piece) => {
+                    let (x, y) = (piece.x, piece.y);
+                    // figure out which player made the move
+                    if game.player_1 == next_move.author {
+                        next_state.player_1_pieces.push(piece);
+                    } else {
+                        next_state.player_2_pieces.push(piece);
+                    }
+                    next_state.outcome_after_place(game, x, y)
+                }
+                // validation never lets a piece be placed where it cannot go
+                Err(_) => next_state.outcome.clone(),
+            },
+            MoveKind::Resign => Outcome::Resigned {
+                winner: game.opponent(&next_move.author).to_string(),
+            },
+            MoveKind::ClaimTimeout => Outcome::TimedOut {
+                winner: next_move.author.clone(),
+            },
+            MoveKind::OfferDraw => {
+                next_state.draw_offered_by = Some(next_move.author.clone());
+                Outcome::InProgress
+            }
+            MoveKind::AcceptDraw => {
+                next_state.draw_offered_by = None;
+                Outcome::Drawn
+            }
+            MoveKind::DeclineDraw => {
+                next_state.draw_offered_by = None;
+                Outcome::InProgress
+            }
+            MoveKind::RequestTakeback => {
+                next_state.takeback_requested_by = Some(next_move.author.clone());
+                Outcome::InProgress
+            }
+            MoveKind::AcceptTakeback => {
+                if let Some(requester) = next_state.takeback_requested_by.take() {
+                    next_state.take_back(game, &requester, next_move.timestamp);
+                }
+                Outcome::InProgress
+            }
+        };
+        if next_move.move_type.kind().takes_turn() {
+            // playing on instead of answering declines the opponent's offer
+            if next_state.draw_offered_by.as_ref() != Some(&next_move.author) {
+                next_state.draw_offered_by = None;
+            }
+            // only the opponent can move while a takeback is pending, which declines it
+            next_state.takeback_requested_by = None;
+            next_state.clock = self
+                .clock
+                .as_ref()
+                .map(|clock| clock.after_move(game, &next_move.author, next_move.timestamp));
+        }
+
+        // once a game has ended nothing that follows can change how it ended
+        if !self.is_over() {
+            next_state.outcome = outcome;
+        }
+        next_state
+    }
+
+    /// Decides the outcome purely from the board after a piece has been placed at (x, y)
+    fn outcome_after_place(&self, game: &Game, x: usize, y: usize) -> Outcome {
+        let board = Board::set(game, self);
+        if board.wins(x, y, game.win_length) {
+            let winner = if board.0[y][x] == 1 {
+                game.player_1.clone()
+            } else {
+                game.player_2.clone()
+            };
+            Outcome::Won { winner }
+        } else if self.player_1_pieces.len() + self.player_2_pieces.len()
+            == game.width * game.height
+        {
+            // a full board with no line is a draw
+            Outcome::Drawn
+        } else {
+            Outcome::InProgress
+        }
+    }
+}
+
+/**
+ *
+ * A move is valid when it is made by one of the players while the game is in progress and they
+ * still have time. Placing a piece and resigning also need it to be the player's turn, and the
+ * ruleset decides where pieces can be placed.
+ *
+ */
+impl<M: Placement> PlayerMove<M> {
+    pub fn is_valid(&self, game: &Game, game_state: &GameState<M>) -> Result<(), String> {
+        if !game.is_player(&self.author) {
+            return Err("Only the players of this game can make moves in it".into());
+        }
+        is_in_progress(&game_state.outcome)?;
+        if let Some(clock) = &game_state.clock {
+            clock.is_in_time(&self.author, self.timestamp)?;
+        }
+        let kind = self.move_type.kind();
+        if kind.takes_turn() {
+            is_players_turn(&self.author, game, game_state)?;
+        }
+        match kind {
+            MoveKind::Place => self.move_type.place(game, game_state).map(|_| ()),
+            MoveKind::Resign => Ok(()),
+            // the waiting player claims, so this is made out of turn
+            MoveKind::ClaimTimeout => match &game_state.clock {
+                Some(clock) => clock.is_valid_claim(&self.author, self.timestamp),
+                None => Err("This game has no time control".into()),
+            },
+            MoveKind::OfferDraw => offers::can_offer(&game_state.draw_offered_by, "draw"),
+            MoveKind::AcceptDraw | MoveKind::DeclineDraw => {
+                offers::can_answer(&game_state.draw_offered_by, &self.author, "draw")
+            }
+            MoveKind::RequestTakeback => {
+                offers::can_offer(&game_state.takeback_requested_by, "takeback")?;
+                is_most_recent_move(&self.author, game_state)
+            }
+            MoveKind::AcceptTakeback => {
+                offers::can_answer(&game_state.takeback_requested_by, &self.author, "takeback")
+            }
+        }
+    }
+}
+
+fn is_in_progress(outcome: &Outcome) -> Result<(), String> {
+    match outcome {
+        Outcome::InProgress => Ok(()),
+        Outcome::Drawn => Err("This game has ended in a draw".into()),
+        _ => Err("This game has ended".into()),
+    }
+}
+
+/// Takebacks only apply to the move the player made last, before their opponent has replied
+fn is_most_recent_move<M: Placement>(
+    player: &str,
+    game_state: &GameState<M>,
+) -> Result<(), String> {
+    match game_state.last_turn() {
+        Some(last_move) if last_move.author == player => Ok(()),
+        _ => Err("Only the most recent move can be taken back, by the player who made it".into()),
+    }
+}
+
+fn is_players_turn<M: Placement>(
+    player: &str,
+    game: &Game,
+    game_state: &GameState<M>,
+) -> Result<(), String> {
+    if game_state.next_player(game) == player {
+        Ok(())
+    } else if game_state.last_turn().is_none() {
+        Err("The other player must make the first move".into())
+    } else {
+        Err("It is not this player's turn".into())
+    }
+}
+
+/// The pieces on the board, 1 for player 1 and 2 for player 2, indexed by row then column
+pub struct Board(pub Vec<Vec<u8>>);
+
+impl Board {
+    pub fn set<M>(game: &Game, game_state: &GameState<M>) -> Self {
+        let mut board = vec![vec![0u8; game.width]; game.height];
+        game_state
+            .player_1_pieces
+            .iter()
+            .for_each(|Piece { x, y }| board[*y][*x] = 1);
+        game_state
+            .player_2_pieces
+            .iter()
+            .for_each(|Piece { x, y }| board[*y][*x] = 2);
+        Self(board)
+    }
+
+    pub fn get(&self, x: isize, y: isize) -> Option<u8> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.0
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .cloned()
+    }
+
+    fn wins(&self, x: usize, y: usize, win_length: usize) -> bool {
+        let val = self.0[y][x];
+        if val == 0 {
+            return false;
+        }
+        // look along the horizontal, vertical, main diagonal and anti diagonal through the position
+        [(1, 0), (0, 1), (1, 1), (1, -1)].iter().any(|&(dx, dy)| {
+            // count the run of pieces either side of the position
+            let run = |sign: isize| {
+                (1..)
+                    .take_while(|&step: &isize| {
+                        self.get(x as isize + sign * step * dx, y as isize + sign * step * dy)
+                            == Some(val)
+                    })
+                    .count()
+            };
+            1 + run(1) + run(-1) >= win_length
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::BTreeMap;
+
+use super::GameResult;
+
+/// The rating every player starts from
+pub const INITIAL_RATING: f64 = 1500.0;
+/// The most a rating can move after a single game
+pub const K_FACTOR: f64 = 32.0;
+
+/// The score `rating` is expected to make against `opponent_rating`, between 0 and 1
+pub fn expected_score(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0))
+}
+
+/// Replays `results`, oldest first, updating both players' ratings after each game
+pub fn ratings(results: &[GameResult]) -> BTreeMap<String, f64> {
+    let mut ratings = BTreeMap::new();
+    for result in results {
+        let rating = |player: &str| *ratings.get(player).unwrap_or(&INITIAL_RATING);
+        let (rating_1, rating_2) = (rating(&result.player_1), rating(&result.player_2));
+        let change =
+            K_FACTOR * (result.score(&result.player_1) - expected_score(rating_1, rating_2));
+        ratings.insert(result.player_1.clone(), rating_1 + change);
+        ratings.insert(result.player_2.clone(), rating_2 - change);
+    }
+    ratings
+}
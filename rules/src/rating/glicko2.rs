@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use super::GameResult;
+
+/// Converts between the Glicko rating scale and the Glicko-2 scale used in the calculations
+const SCALE: f64 = 173.7178;
+/// How far the volatility can change from one rating period to the next
+pub const TAU: f64 = 0.5;
+/// How closely the new volatility is solved for
+const CONVERGENCE: f64 = 0.000_001;
+
+/**
+ *
+ * Glicko-2, as described by Mark Glickman in "Example of the Glicko-2 system"
+ * (http://www.glicko.net/glicko/glicko2.pdf). Ratings carry a deviation, how uncertain the
+ * rating is, and a volatility, how erratic the player's results have been.
+ *
+ */
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    /// Where every player starts: an average rating that is very uncertain
+    fn default() -> Self {
+        Rating {
+            rating: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+impl Rating {
+    fn mu(&self) -> f64 {
+        (self.rating - 1500.0) / SCALE
+    }
+
+    fn phi(&self) -> f64 {
+        self.deviation / SCALE
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+fn expected_score(mu: f64, opponent_mu: f64, opponent_phi: f64) -> f64 {
+    1.0 / (1.0 + (-g(opponent_phi) * (mu - opponent_mu)).exp())
+}
+
+/// The rating of `player` after one rating period in which they scored `score` against each
+/// opponent in `games`. A period without games only makes the rating less certain.
+pub fn update(player: &Rating, games: &[(Rating, f64)], tau: f64) -> Rating {
+    let (mu, phi, sigma) = (player.mu(), player.phi(), player.volatility);
+    if games.is_empty() {
+        return Rating {
+            deviation: (phi.powi(2) + sigma.powi(2)).sqrt() * SCALE,
+            ..*player
+        };
+    }
+
+    let (inverse_variance, improvement) = games.iter().fold(
+        (0.0, 0.0),
+        |(inverse_variance, improvement), (opponent, score)| {
+            let g = g(opponent.phi());
+            let expected = expected_score(mu, opponent.mu(), opponent.phi());
+            (
+                inverse_variance + g.powi(2) * expected * (1.0 - expected),
+                improvement + g * (score - expected),
+            )
+        },
+    );
+    let variance = 1.0 / inverse_variance;
+    let delta = variance * improvement;
+
+    let sigma = new_volatility(phi, sigma, variance, delta, tau);
+    let phi_star = (phi.powi(2) + sigma.powi(2)).sqrt();
+    let phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / variance).sqrt();
+    Rating {
+        rating: (mu + phi.powi(2) * improvement) * SCALE + 1500.0,
+        deviation: phi * SCALE,
+        volatility: sigma,
+    }
+}
+
+/// Solves for the new volatility with the Illinois algorithm, step 5 of Glickman's example
+fn new_volatility(phi: f64, sigma: f64, variance: f64, delta: f64, tau: f64) -> f64 {
+    let a = sigma.powi(2).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta.powi(2) - phi.powi(2) - variance - ex)
+            / (2.0 * (phi.powi(2) + variance + ex).powi(2))
+            - (x - a) / tau.powi(2)
+    };
+
+    let mut upper = a;
+    let mut lower = if delta.powi(2) > phi.powi(2) + variance {
+        (delta.powi(2) - phi.powi(2) - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * tau) < 0.0 {
+            k += 1.0;
+        }
+        a - k * tau
+    };
+    let (mut f_upper, mut f_lower) = (f(upper), f(lower));
+    while (lower - upper).abs() > CONVERGENCE {
+        let next = upper + (upper - lower) * f_upper / (f_lower - f_upper);
+        let f_next = f(next);
+        if f_next * f_lower <= 0.0 {
+            upper = lower;
+            f_upper = f_lower;
+        } else {
+            f_upper /= 2.0;
+        }
+        lower = next;
+        f_lower = f_next;
+    }
+    (upper / 2.0).exp()
+}
+
+/// Replays `results`, oldest first, treating each game as a rating period of its own for the
+/// two players in it
+pub fn ratings(results: &[GameResult]) -> BTreeMap<String, Rating> {
+    let mut ratings: BTreeMap<String, Rating> = BTreeMap::new();
+    for result in results {
+        let rating = |player: &str| ratings.get(player).cloned().unwrap_or_default();
+        let (rating_1, rating_2) = (rating(&result.player_1), rating(&result.player_2));
+        let score_1 = result.score(&result.player_1);
+        ratings.insert(
+            result.player_1.clone(),
+            update(&rating_1, &[(rating_2, score_1)], TAU),
+        );
+        ratings.insert(
+            result.player_2.clone(),
+            update(&rating_2, &[(rating_1, 1.0 - score_1)], TAU),
+        );
+    }
+    ratings
+}
@@ -0,0 +1,97 @@
+/**
+ *
+ * Ratings worked out from nothing but the results of finished games, so that anyone holding
+ * the same results, in the same order, comes to exactly the same ratings.
+ *
+ */
+pub mod elo;
+pub mod glicko2;
+
+#[cfg(test)]
+mod tests;
+
+use std::str::FromStr;
+
+use crate::Outcome;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum RatingSystem {
+    Elo,
+    Glicko2,
+}
+
+// `#[default]` variants are newer than the toolchain the zome is built with
+#[allow(clippy::derivable_impls)]
+impl Default for RatingSystem {
+    fn default() -> Self {
+        RatingSystem::Elo
+    }
+}
+
+impl FromStr for RatingSystem {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "elo" => Ok(RatingSystem::Elo),
+            "glicko2" => Ok(RatingSystem::Glicko2),
+            _ => Err(format!(
+                "Unknown rating system {}, expected elo or glicko2",
+                name
+            )),
+        }
+    }
+}
+
+/// A player's rating, with its deviation and volatility when the rating system has them
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PlayerRating {
+    pub player: String,
+    pub rating: f64,
+    pub deviation: Option<f64>,
+    pub volatility: Option<f64>,
+}
+
+/// A finished game between two players, as far as their ratings are concerned
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GameResult {
+    pub player_1: String,
+    pub player_2: String,
+    pub outcome: Outcome,
+}
+
+impl GameResult {
+    /// The points `player` scored in this game: 1 for a win, 0.5 for a draw and 0 for a loss
+    pub fn score(&self, player: &str) -> f64 {
+        match self.outcome.winner() {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+/// Rates every player in `results`, which must be in the order the games finished.
+/// The same results always give the same ratings, ordered by player.
+pub fn ratings(system: RatingSystem, results: &[GameResult]) -> Vec<PlayerRating> {
+    match system {
+        RatingSystem::Elo => elo::ratings(results)
+            .into_iter()
+            .map(|(player, rating)| PlayerRating {
+                player,
+                rating,
+                deviation: None,
+                volatility: None,
+            })
+            .collect(),
+        RatingSystem::Glicko2 => glicko2::ratings(results)
+            .into_iter()
+            .map(|(player, rating)| PlayerRating {
+                player,
+                rating: rating.rating,
+                deviation: Some(rating.deviation),
+                volatility: Some(rating.volatility),
+            })
+            .collect(),
+    }
+}
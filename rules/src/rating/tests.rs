@@ -0,0 +1,102 @@
+use super::glicko2::{self, Rating};
+use super::{elo, ratings, GameResult, RatingSystem};
+use crate::Outcome;
+
+fn result(player_1: &str, player_2: &str, outcome: Outcome) -> GameResult {
+    GameResult {
+        player_1: player_1.into(),
+        player_2: player_2.into(),
+        outcome,
+    }
+}
+
+fn won_by(winner: &str) -> Outcome {
+    Outcome::Won {
+        winner: winner.into(),
+    }
+}
+
+#[test]
+fn elo_moves_evenly_matched_players_by_half_the_k_factor() {
+    let ratings = elo::ratings(&[result("alice", "bob", won_by("alice"))]);
+    assert_eq!(ratings["alice"], elo::INITIAL_RATING + elo::K_FACTOR / 2.0);
+    assert_eq!(ratings["bob"], elo::INITIAL_RATING - elo::K_FACTOR / 2.0);
+
+    let ratings = elo::ratings(&[result("alice", "bob", Outcome::Drawn)]);
+    assert_eq!(ratings["alice"], elo::INITIAL_RATING);
+}
+
+#[test]
+fn elo_rewards_upsets_more_than_expected_wins() {
+    let ratings = elo::ratings(&[
+        result("alice", "bob", won_by("alice")),
+        result("bob", "alice", won_by("bob")),
+    ]);
+    // bob was rated below alice when he beat her, so he gains back more than he lost
+    assert!(ratings["bob"] > elo::INITIAL_RATING);
+    // points are only ever exchanged between the two players
+    let total: f64 = ratings.values().sum();
+    assert!((total - 2.0 * elo::INITIAL_RATING).abs() < 1e-9);
+}
+
+fn rating(rating: f64, deviation: f64) -> Rating {
+    Rating {
+        rating,
+        deviation,
+        ..Rating::default()
+    }
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "expected {} to be within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+/// The worked example from Glickman's "Example of the Glicko-2 system"
+#[test]
+fn glicko2_matches_the_published_example() {
+    let player = rating(1500.0, 200.0);
+    let games = [
+        (rating(1400.0, 30.0), 1.0),
+        (rating(1550.0, 100.0), 0.0),
+        (rating(1700.0, 300.0), 0.0),
+    ];
+    let updated = glicko2::update(&player, &games, 0.5);
+    assert_close(updated.rating, 1464.06, 0.01);
+    assert_close(updated.deviation, 151.52, 0.01);
+    assert_close(updated.volatility, 0.05999, 0.00001);
+}
+
+#[test]
+fn glicko2_only_grows_the_deviation_without_games() {
+    let player = rating(1500.0, 200.0);
+    let updated = glicko2::update(&player, &[], 0.5);
+    assert_eq!(updated.rating, player.rating);
+    assert_close(updated.deviation, 200.2714, 0.0001);
+}
+
+#[test]
+fn ratings_are_reproducible_and_ordered_by_player() {
+    let results = vec![
+        result("carol", "alice", won_by("carol")),
+        result("alice", "bob", Outcome::Drawn),
+        result(
+            "bob",
+            "carol",
+            Outcome::Resigned {
+                winner: "bob".into(),
+            },
+        ),
+    ];
+    for &system in &[RatingSystem::Elo, RatingSystem::Glicko2] {
+        let rated = ratings(system, &results);
+        assert_eq!(rated, ratings(system, &results));
+        let players: Vec<&str> = rated.iter().map(|r| r.player.as_str()).collect();
+        assert_eq!(players, vec!["alice", "bob", "carol"]);
+    }
+}
@@ -0,0 +1,160 @@
+use super::{GameState, Move, MoveType};
+use crate::placement::{Board, Piece};
+use crate::{Game, Outcome};
+
+/**
+ *
+ * A computer player for tic-tac-toe. It searches the game tree with minimax and alpha-beta
+ * pruning, evolving real `GameState`s so it plays by exactly the same rules as everyone else.
+ *
+ * Positions with few enough empty cells (including every classic 3x3 game) are searched to the
+ * end which gives perfect play. On larger boards the search stops after a few moves and scores
+ * the position with a heuristic that counts the lines each player could still complete.
+ *
+ */
+/// Positions with at most this many empty cells are searched all the way to the end
+const FULL_SEARCH_CELLS: usize = 9;
+/// How many moves ahead to search when the whole tree is too large
+const SEARCH_DEPTH: usize = 3;
+const WIN_SCORE: i64 = 1 << 60;
+
+/// Returns the best move for whoever's turn it is, or `None` if the game is over
+pub fn best_move(game: &Game, game_state: &GameState) -> Option<MoveType> {
+    if game_state.is_over() {
+        return None;
+    }
+    let player = game_state.next_player(game);
+    let candidates = candidate_moves(game, game_state);
+    let empty_cells = game.width * game.height
+        - game_state.player_1_pieces.len()
+        - game_state.player_2_pieces.len();
+    // large boards have few candidates early on, but searching those to the end never finishes
+    let depth = if empty_cells <= FULL_SEARCH_CELLS {
+        empty_cells
+    } else {
+        SEARCH_DEPTH
+    };
+
+    let mut alpha = -WIN_SCORE;
+    let mut best = None;
+    for Piece { x, y } in candidates {
+        let next_state = game_state.evolve(game, &hypothetical_move(player, x, y));
+        let score = -negamax(game, &next_state, depth - 1, -WIN_SCORE, -alpha);
+        if best.is_none() || score > alpha {
+            alpha = score;
+            best = Some(MoveType::Place { x, y });
+        }
+    }
+    best
+}
+
+/// Scores a position from the point of view of the player whose turn it is
+fn negamax(game: &Game, game_state: &GameState, depth: usize, mut alpha: i64, beta: i64) -> i64 {
+    let player = game_state.next_player(game);
+    match &game_state.outcome {
+        Outcome::InProgress => {}
+        Outcome::Drawn => return 0,
+        // prefer quick wins and slow losses
+        outcome => {
+            let score = WIN_SCORE - game_state.moves.len() as i64;
+            return if outcome.winner() == Some(player) {
+                score
+            } else {
+                -score
+            };
+        }
+    }
+    if depth == 0 {
+        return heuristic(game, game_state, player);
+    }
+
+    let mut best = -WIN_SCORE;
+    for Piece { x, y } in candidate_moves(game, game_state) {
+        let next_state = game_state.evolve(game, &hypothetical_move(player, x, y));
+        let score = -negamax(game, &next_state, depth - 1, -beta, -alpha);
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+/// Empty cells worth considering, most central first so the best moves tend to be searched early.
+/// On large boards only cells next to an existing piece are considered.
+fn candidate_moves(game: &Game, game_state: &GameState) -> Vec<Piece> {
+    let board = Board::set(game, game_state);
+    let empty = (0..game.height)
+        .flat_map(|y| (0..game.width).map(move |x| Piece { x, y }))
+        .filter(|&Piece { x, y }| board.0[y][x] == 0);
+    let mut candidates: Vec<Piece> = if empty.clone().count() <= FULL_SEARCH_CELLS {
+        empty.collect()
+    } else {
+        let near_piece = |&Piece { x, y }: &Piece| {
+            (-1..=1).any(|dy| {
+                (-1..=1).any(|dx| board.get(x as isize + dx, y as isize + dy).unwrap_or(0) != 0)
+            })
+        };
+        let near: Vec<Piece> = empty.clone().filter(near_piece).collect();
+        if near.is_empty() {
+            empty.collect()
+        } else {
+            near
+        }
+    };
+    let distance_from_centre = |piece: &Piece| {
+        let dx = 2 * piece.x as isize - game.width as isize + 1;
+        let dy = 2 * piece.y as isize - game.height as isize + 1;
+        dx.abs() + dy.abs()
+    };
+    candidates.sort_by_key(distance_from_centre);
+    if game_state.moves.is_empty() && candidates.len() > FULL_SEARCH_CELLS {
+        // on an empty large board there is nothing to compare, just take the centre
+        candidates.truncate(1);
+    }
+    candidates
+}
+
+/// Counts every run of `win_length` cells that only one player has pieces in. The more pieces in
+/// such a run the more it is worth, and the opponent's runs count against the player.
+fn heuristic(game: &Game, game_state: &GameState, player: &str) -> i64 {
+    let board = Board::set(game, game_state);
+    let mine = if player == game.player_1 { 1 } else { 2 };
+    let k = game.win_length as isize;
+    let mut score = 0;
+    for y in 0..game.height as isize {
+        for x in 0..game.width as isize {
+            for &(dx, dy) in [(1, 0), (0, 1), (1, 1), (1, -1)].iter() {
+                let window: Option<Vec<u8>> =
+                    (0..k).map(|i| board.get(x + i * dx, y + i * dy)).collect();
+                if let Some(window) = window {
+                    let own = window.iter().filter(|&&cell| cell == mine).count();
+                    let theirs = window
+                        .iter()
+                        .filter(|&&cell| cell != mine && cell != 0)
+                        .count();
+                    if theirs == 0 && own > 0 {
+                        score += window_weight(own);
+                    } else if own == 0 && theirs > 0 {
+                        score -= window_weight(theirs);
+                    }
+                }
+            }
+        }
+    }
+    score
+}
+
+fn window_weight(pieces: usize) -> i64 {
+    1 << (3 * pieces).min(40)
+}
+
+fn hypothetical_move(author: &str, x: usize, y: usize) -> Move {
+    // the search looks at positions, not clocks, so the time a move is made does not matter
+    Move {
+        author: author.to_string(),
+        move_type: MoveType::Place { x, y },
+        timestamp: 0,
+    }
+}
@@ -0,0 +1,64 @@
+pub mod engine;
+pub mod moves;
+/**
+ * All of this code is specific to the game Checkers
+ * By changing the moves, state, reducer and validation rules you can implement you own game.
+ */
+pub mod state;
+pub mod validation;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::{moves::MoveType, state::GameState};
+use crate::{Game, GameRules, Outcome, PlayerMove};
+
+pub type Move = PlayerMove<MoveType>;
+
+/// Tic-tac-toe, or any m,n,k-game, played within the generic game framework
+pub struct TicTacToe;
+
+impl GameRules for TicTacToe {
+    type State = GameState;
+    type Move = MoveType;
+
+    const DEFAULT_WIDTH: usize = state::DEFAULT_BOARD_SIZE;
+    const DEFAULT_HEIGHT: usize = state::DEFAULT_BOARD_SIZE;
+    const DEFAULT_WIN_LENGTH: usize = state::DEFAULT_BOARD_SIZE;
+
+    fn initial(game: &Game) -> GameState {
+        GameState::initial(game)
+    }
+
+    fn evolve(game: &Game, state: &GameState, next_move: &Move) -> GameState {
+        state.evolve(game, next_move)
+    }
+
+    fn is_valid(game: &Game, state: &GameState, next_move: &Move) -> Result<(), String> {
+        next_move.is_valid(game, state)
+    }
+
+    fn is_valid_game(game: &Game) -> Result<(), String> {
+        validation::is_valid_board(game)
+    }
+
+    fn render(game: &Game, state: &GameState, viewer: Option<&str>) -> String {
+        state.render(game, viewer)
+    }
+
+    fn describe_moves() -> Vec<MoveType> {
+        MoveType::describe()
+    }
+
+    fn outcome(state: &GameState) -> &Outcome {
+        &state.outcome
+    }
+
+    fn next_player<'a>(game: &'a Game, state: &GameState) -> &'a str {
+        state.next_player(game)
+    }
+
+    fn best_move(game: &Game, state: &GameState) -> Option<MoveType> {
+        engine::best_move(game, state)
+    }
+}
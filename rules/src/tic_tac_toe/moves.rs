@@ -0,0 +1,69 @@
+use super::GameState;
+use crate::placement::{Board, MoveKind, Piece, Placement};
+use crate::Game;
+
+/**
+ *
+ * The MoveType enum defines all the types of moves that are valid in your game and the
+ * data they carry. In Checkers you can move a piece (MovePiece) from a location to another location.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum MoveType {
+    Place { x: usize, y: usize },
+    Resign,
+    ClaimTimeout,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    RequestTakeback,
+    AcceptTakeback,
+}
+
+impl MoveType {
+    pub fn describe() -> Vec<MoveType> {
+        vec![
+            MoveType::Place { x: 0, y: 0 },
+            MoveType::Resign,
+            MoveType::ClaimTimeout,
+            MoveType::OfferDraw,
+            MoveType::AcceptDraw,
+            MoveType::DeclineDraw,
+            MoveType::RequestTakeback,
+            MoveType::AcceptTakeback,
+        ]
+    }
+}
+
+/// A piece can be placed on any empty cell of the board
+impl Placement for MoveType {
+    fn kind(&self) -> MoveKind {
+        match self {
+            MoveType::Place { .. } => MoveKind::Place,
+            MoveType::Resign => MoveKind::Resign,
+            MoveType::ClaimTimeout => MoveKind::ClaimTimeout,
+            MoveType::OfferDraw => MoveKind::OfferDraw,
+            MoveType::AcceptDraw => MoveKind::AcceptDraw,
+            MoveType::DeclineDraw => MoveKind::DeclineDraw,
+            MoveType::RequestTakeback => MoveKind::RequestTakeback,
+            MoveType::AcceptTakeback => MoveKind::AcceptTakeback,
+        }
+    }
+
+    fn place(&self, game: &Game, state: &GameState) -> Result<Piece, String> {
+        match *self {
+            MoveType::Place { x, y } => {
+                let pos = Piece { x, y };
+                pos.is_in_bounds(game)?;
+                pos.is_empty(state)?;
+                Ok(pos)
+            }
+            _ => Err("Only placing a piece puts it on the board".into()),
+        }
+    }
+
+    fn render_board(board: &Board) -> String {
+        super::state::render_board(board)
+    }
+}
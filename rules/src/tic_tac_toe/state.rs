@@ -0,0 +1,45 @@
+use super::MoveType;
+use crate::placement::{self, Board};
+
+pub use crate::placement::Piece;
+
+/**
+ *
+ * As a game author you get to decide what the State object of your game looks like.
+ * Tic-tac-toe keeps the state every placement game does, see `placement::GameState`,
+ * which includes all of the previous moves as well.
+ *
+ */
+pub type GameState = placement::GameState<MoveType>;
+
+/// Classic tic-tac-toe is played on a 3x3 board with three in a row to win
+pub const DEFAULT_BOARD_SIZE: usize = 3;
+pub const MAX_BOARD_SIZE: usize = 32;
+
+/// Draws the pieces on the board, see `Placement::render_board`
+pub(super) fn render_board(board: &Board) -> String {
+    let width = board.0.first().map(|row| row.len()).unwrap_or(0);
+    // every cell is as wide as the widest column label
+    let cell_width = width.saturating_sub(1).to_string().len();
+    let header = (0..width)
+        .map(|x| format!("{:>w$}", x, w = cell_width))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut lines = format!("  x  {}\ny\n", header);
+    for (row, y) in board.0.iter().enumerate() {
+        lines.push_str(&format!("{:<4}", row));
+        for &x in y.iter() {
+            lines.push_str(&format!(
+                "|{:>w$}",
+                match x {
+                    2 => 'X',
+                    1 => 'O',
+                    _ => ' ',
+                },
+                w = cell_width
+            ));
+        }
+        lines.push_str("|\n");
+    }
+    lines
+}
@@ -0,0 +1,386 @@
+use proptest::prelude::*;
+
+use super::engine::best_move;
+use super::state::Piece;
+use super::{GameState, Move, MoveType};
+use crate::{Game, Outcome, TimeControl};
+
+fn new_game(width: usize, height: usize, win_length: usize) -> Game {
+    Game {
+        player_1: "player_1".into(),
+        player_2: "player_2".into(),
+        created_at: 0,
+        width,
+        height,
+        win_length,
+        time_control: None,
+        player_1_moves_first: false,
+    }
+}
+
+fn new_move(author: &str, move_type: MoveType) -> Move {
+    Move {
+        author: author.into(),
+        move_type,
+        timestamp: 0,
+    }
+}
+
+/// Scans every cell in every direction for a run of `win_length` pieces, independently of `evolve`
+fn brute_force_has_line(pieces: &[Piece], game: &Game) -> bool {
+    let occupied = |x: isize, y: isize| {
+        pieces
+            .iter()
+            .any(|p| p.x as isize == x && p.y as isize == y)
+    };
+    let k = game.win_length as isize;
+    (0..game.width as isize).any(|x| {
+        (0..game.height as isize).any(|y| {
+            [(1, 0), (0, 1), (1, 1), (1, -1)]
+                .iter()
+                .any(|&(dx, dy)| (0..k).all(|i| occupied(x + i * dx, y + i * dy)))
+        })
+    })
+}
+
+/// Plays legal moves chosen by `choices` until the game ends, checking every intermediate state
+/// against the brute force scanner. Returns the final state and the player who would move next.
+fn play_out(game: &Game, choices: &[usize]) -> Result<(GameState, String), TestCaseError> {
+    let mut state = GameState::initial(game);
+    // player 2 goes first by convention
+    let mut author = game.player_2.clone();
+
+    for choice in choices {
+        if state.is_over() {
+            break;
+        }
+        let empty: Vec<Piece> = (0..game.height)
+            .flat_map(|y| (0..game.width).map(move |x| Piece { x, y }))
+            .filter(|piece| piece.is_empty(&state).is_ok())
+            .collect();
+        let Piece { x, y } = empty[choice % empty.len()].clone();
+        let next_move = new_move(&author, MoveType::Place { x, y });
+        prop_assert!(next_move.is_valid(game, &state).is_ok());
+
+        state = state.evolve(game, &next_move);
+
+        let (mover_pieces, other_pieces) = if author == game.player_1 {
+            (&state.player_1_pieces, &state.player_2_pieces)
+        } else {
+            (&state.player_2_pieces, &state.player_1_pieces)
+        };
+        // play stops as soon as anyone has a line so only the mover can have one now
+        prop_assert!(!brute_force_has_line(other_pieces, game));
+        let expected = if brute_force_has_line(mover_pieces, game) {
+            Outcome::Won {
+                winner: author.clone(),
+            }
+        } else if state.player_1_pieces.len() + state.player_2_pieces.len()
+            == game.width * game.height
+        {
+            Outcome::Drawn
+        } else {
+            Outcome::InProgress
+        };
+        prop_assert_eq!(&state.outcome, &expected);
+
+        author = game.opponent(&author).to_string();
+    }
+    Ok((state, author))
+}
+
+/// Board dimensions, a win length that fits and enough choices to fill the board
+fn random_game() -> impl Strategy<Value = (Game, Vec<usize>)> {
+    (1usize..=6, 1usize..=6).prop_flat_map(|(width, height)| {
+        (
+            (1..=width.max(height)).prop_map(move |k| new_game(width, height, k)),
+            prop::collection::vec(any::<usize>(), width * height),
+        )
+    })
+}
+
+proptest! {
+    #[test]
+    fn outcome_matches_brute_force_scan((game, choices) in random_game()) {
+        let (state, _) = play_out(&game, &choices)?;
+        // a board can only be filled so many times
+        prop_assert!(state.is_over());
+    }
+
+    #[test]
+    fn outcome_survives_appended_moves((game, choices) in random_game()) {
+        let (state, next_player) = play_out(&game, &choices)?;
+
+        // replaying invalid moves after the end, including a resignation, must not change the result
+        let appended = [
+            new_move(&next_player, MoveType::Place { x: 0, y: 0 }),
+            new_move(&game.player_1, MoveType::Resign),
+        ];
+        let replayed = appended
+            .iter()
+            .fold(state.clone(), |state, next_move| state.evolve(&game, next_move));
+        prop_assert_eq!(replayed.outcome, state.outcome);
+    }
+}
+
+/// Plays the given placements in turn, starting with player 2
+fn play(game: &Game, placements: &[(usize, usize)]) -> GameState {
+    let mut state = GameState::initial(game);
+    for &(x, y) in placements {
+        let author = state.next_player(game).to_string();
+        state = state.evolve(game, &new_move(&author, MoveType::Place { x, y }));
+    }
+    state
+}
+
+#[test]
+fn engine_takes_a_winning_move() {
+    let game = new_game(3, 3, 3);
+    let state = play(&game, &[(0, 0), (1, 1), (1, 0), (2, 2)]);
+    assert_eq!(
+        best_move(&game, &state),
+        Some(MoveType::Place { x: 2, y: 0 })
+    );
+}
+
+#[test]
+fn engine_blocks_a_losing_line() {
+    let game = new_game(3, 3, 3);
+    let state = play(&game, &[(0, 0), (1, 1), (0, 1)]);
+    assert_eq!(
+        best_move(&game, &state),
+        Some(MoveType::Place { x: 0, y: 2 })
+    );
+}
+
+#[test]
+fn engine_draws_against_itself() {
+    let game = new_game(3, 3, 3);
+    let mut state = GameState::initial(&game);
+    while let Some(move_type) = best_move(&game, &state) {
+        let author = state.next_player(&game).to_string();
+        state = state.evolve(&game, &new_move(&author, move_type));
+    }
+    assert_eq!(state.outcome, Outcome::Drawn);
+}
+
+#[test]
+fn engine_blocks_a_four_on_a_large_board() {
+    let game = new_game(15, 15, 5);
+    let state = play(
+        &game,
+        &[(5, 7), (4, 7), (6, 7), (0, 0), (7, 7), (0, 2), (8, 7)],
+    );
+    assert_eq!(
+        best_move(&game, &state),
+        Some(MoveType::Place { x: 9, y: 7 })
+    );
+}
+
+#[test]
+fn engine_replies_quickly_on_a_large_board() {
+    let game = new_game(15, 15, 5);
+    let state = play(&game, &[(7, 7)]);
+    let started = std::time::Instant::now();
+    assert!(best_move(&game, &state).is_some());
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+fn timed_game(time_control: TimeControl) -> Game {
+    Game {
+        time_control: Some(time_control),
+        ..new_game(3, 3, 3)
+    }
+}
+
+fn timed_move(author: &str, move_type: MoveType, timestamp: u64) -> Move {
+    Move {
+        timestamp,
+        ..new_move(author, move_type)
+    }
+}
+
+#[test]
+fn timeout_can_be_claimed_once_the_move_time_runs_out() {
+    let game = timed_game(TimeControl::PerMove { seconds: 30 });
+    let state = GameState::initial(&game).evolve(
+        &game,
+        &timed_move("player_2", MoveType::Place { x: 0, y: 0 }, 10_000),
+    );
+
+    // player 1 has until 40 seconds to reply
+    let early_claim = timed_move("player_2", MoveType::ClaimTimeout, 40_000);
+    assert!(early_claim.is_valid(&game, &state).is_err());
+    let late_move = timed_move("player_1", MoveType::Place { x: 1, y: 1 }, 40_001);
+    assert!(late_move.is_valid(&game, &state).is_err());
+
+    let claim = timed_move("player_2", MoveType::ClaimTimeout, 40_001);
+    assert_eq!(claim.is_valid(&game, &state), Ok(()));
+    let state = state.evolve(&game, &claim);
+    assert_eq!(
+        state.outcome,
+        Outcome::TimedOut {
+            winner: "player_2".into()
+        }
+    );
+}
+
+#[test]
+fn players_cannot_claim_a_timeout_on_their_own_turn() {
+    let game = timed_game(TimeControl::PerMove { seconds: 30 });
+    let state = GameState::initial(&game);
+    let claim = timed_move("player_2", MoveType::ClaimTimeout, 100);
+    assert!(claim.is_valid(&game, &state).is_err());
+}
+
+#[test]
+fn fischer_clocks_spend_time_and_add_the_increment() {
+    let game = timed_game(TimeControl::Fischer {
+        initial: 60,
+        increment: 5,
+    });
+    let state = [(0, 0, 20_000), (1, 1, 50_000), (2, 2, 60_000)]
+        .iter()
+        .fold(GameState::initial(&game), |state, &(x, y, timestamp)| {
+            let author = state.next_player(&game).to_string();
+            let next_move = timed_move(&author, MoveType::Place { x, y }, timestamp);
+            assert_eq!(next_move.is_valid(&game, &state), Ok(()));
+            state.evolve(&game, &next_move)
+        });
+    let clock = state.clock.expect("a timed game has a clock");
+    // player 2 used 20 then 10 seconds, player 1 used 30
+    assert_eq!(clock.player_2.remaining, (60 - 20 + 5 - 10 + 5) * 1000);
+    assert_eq!(clock.player_1.remaining, (60 - 30 + 5) * 1000);
+    assert_eq!(clock.time_left("player_1", 70_000), 25_000);
+}
+
+#[test]
+fn untimed_games_have_no_timeouts() {
+    let game = new_game(3, 3, 3);
+    let state = GameState::initial(&game);
+    assert!(state.clock.is_none());
+    let claim = timed_move("player_1", MoveType::ClaimTimeout, u64::MAX);
+    assert!(claim.is_valid(&game, &state).is_err());
+}
+
+#[test]
+fn draw_offers_can_only_be_answered_by_the_opponent() {
+    let game = new_game(3, 3, 3);
+    let state = GameState::initial(&game);
+    let accept = new_move("player_1", MoveType::AcceptDraw);
+    assert!(accept.is_valid(&game, &state).is_err());
+
+    // offers are made out of turn and do not use up the offering player's turn
+    let offer = new_move("player_1", MoveType::OfferDraw);
+    assert_eq!(offer.is_valid(&game, &state), Ok(()));
+    let state = state.evolve(&game, &offer);
+    assert_eq!(state.draw_offered_by, Some("player_1".to_string()));
+    assert_eq!(state.next_player(&game), "player_2");
+    assert!(new_move("player_1", MoveType::OfferDraw)
+        .is_valid(&game, &state)
+        .is_err());
+    assert!(accept.is_valid(&game, &state).is_err());
+
+    let accept = new_move("player_2", MoveType::AcceptDraw);
+    assert_eq!(accept.is_valid(&game, &state), Ok(()));
+    let state = state.evolve(&game, &accept);
+    assert_eq!(state.outcome, Outcome::Drawn);
+    assert_eq!(state.draw_offered_by, None);
+}
+
+#[test]
+fn draw_offers_are_declined_by_answering_or_playing_on() {
+    let game = new_game(3, 3, 3);
+    let offer = new_move("player_1", MoveType::OfferDraw);
+    let state = GameState::initial(&game).evolve(&game, &offer);
+
+    let decline = new_move("player_2", MoveType::DeclineDraw);
+    assert_eq!(decline.is_valid(&game, &state), Ok(()));
+    let declined = state.evolve(&game, &decline);
+    assert_eq!(declined.draw_offered_by, None);
+    assert_eq!(declined.outcome, Outcome::InProgress);
+
+    let place = new_move("player_2", MoveType::Place { x: 0, y: 0 });
+    assert_eq!(place.is_valid(&game, &state), Ok(()));
+    let played_on = state.evolve(&game, &place);
+    assert_eq!(played_on.draw_offered_by, None);
+    assert!(new_move("player_1", MoveType::AcceptDraw)
+        .is_valid(&game, &played_on)
+        .is_err());
+}
+
+#[test]
+fn takebacks_undo_the_requesters_last_move_once_accepted() {
+    let game = new_game(3, 3, 3);
+    let state = play(&game, &[(0, 0), (1, 1)]);
+    // player 2 moved first so player 1 made the most recent move
+    let not_last = new_move("player_2", MoveType::RequestTakeback);
+    assert!(not_last.is_valid(&game, &state).is_err());
+    let early_accept = new_move("player_2", MoveType::AcceptTakeback);
+    assert!(early_accept.is_valid(&game, &state).is_err());
+
+    let request = new_move("player_1", MoveType::RequestTakeback);
+    assert_eq!(request.is_valid(&game, &state), Ok(()));
+    let state = state.evolve(&game, &request);
+    assert!(new_move("player_1", MoveType::AcceptTakeback)
+        .is_valid(&game, &state)
+        .is_err());
+
+    let accept = new_move("player_2", MoveType::AcceptTakeback);
+    assert_eq!(accept.is_valid(&game, &state), Ok(()));
+    let state = state.evolve(&game, &accept);
+    assert!(state.player_1_pieces.is_empty());
+    assert_eq!(state.player_2_pieces, vec![Piece { x: 0, y: 0 }]);
+    assert_eq!(state.takeback_requested_by, None);
+    assert_eq!(state.next_player(&game), "player_1");
+    let retry = new_move("player_1", MoveType::Place { x: 1, y: 1 });
+    assert_eq!(retry.is_valid(&game, &state), Ok(()));
+}
+
+#[test]
+fn takebacks_lapse_when_the_opponent_plays_on() {
+    let game = new_game(3, 3, 3);
+    let state = play(&game, &[(0, 0)]);
+    let request = new_move("player_2", MoveType::RequestTakeback);
+    let state = state.evolve(&game, &request);
+    let state = state.evolve(&game, &new_move("player_1", MoveType::Place { x: 1, y: 1 }));
+    assert_eq!(state.takeback_requested_by, None);
+    assert!(new_move("player_1", MoveType::AcceptTakeback)
+        .is_valid(&game, &state)
+        .is_err());
+    // player 2's move is no longer the most recent one
+    assert!(request.is_valid(&game, &state).is_err());
+}
+
+#[test]
+fn player_1_can_be_given_the_first_move() {
+    let game = Game {
+        player_1_moves_first: true,
+        ..timed_game(TimeControl::PerMove { seconds: 30 })
+    };
+    let state = GameState::initial(&game);
+    assert_eq!(state.next_player(&game), "player_1");
+    assert_eq!(
+        state.clock.as_ref().map(|clock| clock.running.as_str()),
+        Some("player_1")
+    );
+    let out_of_turn = new_move("player_2", MoveType::Place { x: 0, y: 0 });
+    assert!(out_of_turn.is_valid(&game, &state).is_err());
+    let first = new_move("player_1", MoveType::Place { x: 0, y: 0 });
+    assert_eq!(first.is_valid(&game, &state), Ok(()));
+    assert_eq!(state.evolve(&game, &first).next_player(&game), "player_2");
+}
+
+#[test]
+fn only_the_players_can_make_moves() {
+    let game = new_game(3, 3, 3);
+    let state = GameState::initial(&game).evolve(&game, &new_move("player_1", MoveType::OfferDraw));
+    for move_type in &[
+        MoveType::Place { x: 0, y: 0 },
+        MoveType::OfferDraw,
+        MoveType::AcceptDraw,
+    ] {
+        let spectator_move = new_move("spectator", move_type.clone());
+        assert!(spectator_move.is_valid(&game, &state).is_err());
+    }
+}
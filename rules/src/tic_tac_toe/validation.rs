@@ -0,0 +1,28 @@
+use super::state::MAX_BOARD_SIZE;
+use crate::Game;
+
+/**
+ *
+ * Moves are validated by the framework every placement game shares, see `placement`, which
+ * leaves a ruleset to decide where its pieces can go in `Placement::place`.
+ *
+ * What is left to validate here is the game itself. It is useful to provide descriptive error
+ * strings as these can be visible to the end user.
+ *
+ */
+/// Checks the board dimensions and win length a game was created with are playable
+pub fn is_valid_board(game: &Game) -> Result<(), String> {
+    if game.width == 0 || game.height == 0 {
+        return Err("The board must have at least one row and one column".into());
+    }
+    if game.width > MAX_BOARD_SIZE || game.height > MAX_BOARD_SIZE {
+        return Err(format!(
+            "The board cannot be larger than {}x{}",
+            MAX_BOARD_SIZE, MAX_BOARD_SIZE
+        ));
+    }
+    if game.win_length == 0 || game.win_length > game.width.max(game.height) {
+        return Err("The win length must fit on the board".into());
+    }
+    Ok(())
+}
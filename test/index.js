@@ -13,13 +13,19 @@ process.on('unhandledRejection', error => {
   console.error('got unhandledRejection:', error);
 });
 
+// some scenarios commit entries no honest agent would, through zome functions that only work
+// in a DNA packaged with `RUSTFLAGS="--cfg test_hooks" hc package`
 const dnaPath = path.join(__dirname, "../dist/tic-tac-toe.dna.json")
 const dna = Diorama.dna(dnaPath, 'tic-tac-toe')
 
+// whether a call failed for `reason`, rather than for some other reason such as a missing hook
+const rejectedWith = (result, reason) => JSON.stringify(result.Err || '').includes(reason)
+
 const diorama = new Diorama({
   instances: {
     alice: dna,
     bob: dna,
+    carol: dna,
   },
   bridges: [],
   debugLog: false,
@@ -28,10 +34,11 @@ const diorama = new Diorama({
 })
 
 diorama.registerScenario("Can create and play games", async (s, t, {alice, bob}) => {
+  const now = Date.now()
   console.log("  Alice creates a game with Bob")
   const create_game_result_1 = await alice.callSync('main', 'create_game', {
     opponent: bob.agentId,
-    timestamp: 0
+    timestamp: now
   })
   console.log(create_game_result_1)
   t.equal(create_game_result_1.Ok.length, 46)
@@ -46,7 +53,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 0, y: 0 } },
-      timestamp: 1
+      timestamp: now + 1
     }
   })
   console.log(bad_move_1_result)
@@ -61,7 +68,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 0, y: 0 } },
-      timestamp: 2
+      timestamp: now + 2
     }
   })
   console.log(move_1_result)
@@ -76,7 +83,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 0, y: 0 } },
-      timestamp: 3
+      timestamp: now + 3
     }
   })
   console.log(bad_move_2_result)
@@ -91,7 +98,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 1, y: 1 } },
-      timestamp: 4
+      timestamp: now + 4
     }
   })
   console.log(move_2_result)
@@ -106,7 +113,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 3, y: 0 } },
-      timestamp: 5
+      timestamp: now + 5
     }
   })
   console.log(bad_move_3_result)
@@ -121,7 +128,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 2, y: 0 } },
-      timestamp: 6
+      timestamp: now + 6
     }
   })
   console.log(move_3_result)
@@ -136,7 +143,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 1, y: 2 } },
-      timestamp: 7
+      timestamp: now + 7
     }
   })
   console.log(move_4_result)
@@ -151,7 +158,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 1, y: 0 } },
-      timestamp: 8
+      timestamp: now + 8
     }
   })
   console.log(move_5_result)
@@ -166,7 +173,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_1,
       move_type: { Place: { x: 0, y: 1 } },
-      timestamp: 9
+      timestamp: now + 9
     }
   })
   console.log(bad_move_6_result)
@@ -179,7 +186,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
   console.log("  Bob creates a game with Alice")
   const create_game_result_2 = await bob.callSync('main', 'create_game', {
     opponent: alice.agentId,
-    timestamp: 10
+    timestamp: now + 10
   })
   console.log(create_game_result_2)
   t.equal(create_game_result_2.Ok.length, 46)
@@ -194,7 +201,7 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
     new_move: {
       game: game_address_2,
       move_type: 'Resign',
-      timestamp: 11
+      timestamp: now + 11
     }
   })
   console.log(resign_result)
@@ -205,4 +212,704 @@ diorama.registerScenario("Can create and play games", async (s, t, {alice, bob})
   console.log(game_state)
 })
 
+diorama.registerScenario("A full board with no line is a draw", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now
+  })
+  t.equal(create_game_result.Ok.length, 46)
+  const game_address = create_game_result.Ok
+
+  // X O X
+  // X O O
+  // O X X
+  const moves = [
+    [bob, 0, 0], [alice, 1, 1], [bob, 2, 0], [alice, 1, 0], [bob, 1, 2],
+    [alice, 0, 2], [bob, 0, 1], [alice, 2, 1], [bob, 2, 2],
+  ]
+  for (let i = 0; i < moves.length; i++) {
+    const [player, x, y] = moves[i]
+    const move_result = await player.callSync('main', 'make_move', {
+      new_move: {
+        game: game_address,
+        move_type: { Place: { x, y } },
+        timestamp: now + i + 1
+      }
+    })
+    t.equal(move_result.Err, undefined)
+  }
+
+  const game_state = await alice.callSync('main', 'get_state',{
+    game_address
+  })
+  console.log(game_state)
+  t.equal(game_state.Ok.outcome, 'Drawn')
+
+  console.log("  Alice tries to resign but the game is already drawn")
+  const resign_result = await alice.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: 'Resign',
+      timestamp: now + 10
+    }
+  })
+  console.log(resign_result)
+  t.equal(resign_result.Ok, undefined)
+})
+
+diorama.registerScenario("Can play on a larger board with a custom win length", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now,
+    width: 5,
+    height: 4,
+    win_length: 4
+  })
+  t.equal(create_game_result.Ok.length, 46)
+  const game_address = create_game_result.Ok
+
+  console.log("  Bob tries to play off the side of the board")
+  const bad_move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 5, y: 0 } },
+      timestamp: now + 1
+    }
+  })
+  console.log(bad_move_result)
+  t.equal(bad_move_result.Ok, undefined)
+
+  const moves = [
+    [bob, 4, 3], [alice, 0, 0], [bob, 3, 3], [alice, 0, 1],
+    [bob, 2, 3], [alice, 0, 2], [bob, 1, 3],
+  ]
+  for (let i = 0; i < moves.length; i++) {
+    const [player, x, y] = moves[i]
+    const move_result = await player.callSync('main', 'make_move', {
+      new_move: {
+        game: game_address,
+        move_type: { Place: { x, y } },
+        timestamp: now + i + 2
+      }
+    })
+    t.equal(move_result.Err, undefined)
+  }
+
+  const game_state = await alice.callSync('main', 'get_state',{
+    game_address
+  })
+  console.log(game_state)
+  t.deepEqual(game_state.Ok.outcome, { Won: { winner: bob.agentId } })
+})
+
+diorama.registerScenario("Can play Connect Four", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now,
+    ruleset: 'ConnectFour'
+  })
+  t.equal(create_game_result.Ok.length, 46)
+  const game_address = create_game_result.Ok
+
+  console.log("  Bob tries to place a tic-tac-toe piece")
+  const bad_move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 0, y: 0 } },
+      timestamp: now + 1
+    }
+  })
+  console.log(bad_move_result)
+  t.equal(bad_move_result.Ok, undefined)
+
+  const moves = [
+    [bob, 0], [alice, 1], [bob, 0], [alice, 1],
+    [bob, 0], [alice, 1], [bob, 0],
+  ]
+  for (let i = 0; i < moves.length; i++) {
+    const [player, column] = moves[i]
+    const move_result = await player.callSync('main', 'make_move', {
+      new_move: {
+        game: game_address,
+        move_type: { Drop: { column } },
+        timestamp: now + i + 2
+      }
+    })
+    t.equal(move_result.Err, undefined)
+  }
+
+  const game_state = await alice.callSync('main', 'get_state',{
+    game_address
+  })
+  console.log(game_state)
+  t.deepEqual(game_state.Ok.player_2_pieces[0], { x: 0, y: 5 })
+  t.deepEqual(game_state.Ok.outcome, { Won: { winner: bob.agentId } })
+
+  const integrity_result = await alice.callSync('main', 'get_game_integrity', {
+    game_address
+  })
+  console.log(integrity_result)
+  t.equal(integrity_result.Ok.is_intact, true)
+  t.deepEqual(integrity_result.Ok.forks, [])
+  t.deepEqual(integrity_result.Ok.orphaned_moves, [])
+})
+
+diorama.registerScenario("Can claim a timeout when the opponent runs out of time", async (s, t, {alice, bob}) => {
+  // start the game far enough in the past for Alice's time to have run out
+  const now = Date.now() - 45000
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now,
+    time_control: { PerMove: { seconds: 30 } }
+  })
+  const game_address = create_game_result.Ok
+
+  const move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 0, y: 0 } },
+      timestamp: now + 10000
+    }
+  })
+  t.equal(move_result.Err, undefined)
+
+  console.log("  Bob claims before Alice's time is up")
+  const early_claim_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: "ClaimTimeout",
+      timestamp: now + 30000
+    }
+  })
+  t.equal(early_claim_result.Ok, undefined)
+
+  const claim_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: "ClaimTimeout",
+      timestamp: now + 40001
+    }
+  })
+  t.equal(claim_result.Err, undefined)
+
+  const game_state = await alice.callSync('main', 'get_state', {
+    game_address
+  })
+  console.log(game_state)
+  t.deepEqual(game_state.Ok.outcome, { TimedOut: { winner: bob.agentId } })
+})
+
+diorama.registerScenario("Games are listed for both players", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now
+  })
+  const game_address = create_game_result.Ok
+
+  const move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 0, y: 0 } },
+      timestamp: now + 1000
+    }
+  })
+  t.equal(move_result.Err, undefined)
+
+  const alice_games = await alice.callSync('main', 'list_my_games', {})
+  console.log(alice_games)
+  t.deepEqual(alice_games.Ok, [{
+    address: game_address,
+    opponent: bob.agentId,
+    status: 'MyTurn',
+    last_activity: now + 1000
+  }])
+
+  const bob_games = await bob.callSync('main', 'list_my_games', {})
+  t.equal(bob_games.Ok.length, 1)
+  t.equal(bob_games.Ok[0].status, 'TheirTurn')
+})
+
+diorama.registerScenario("Finished games count towards stats and the leaderboard", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now
+  })
+  const game_address = create_game_result.Ok
+
+  const resign_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: "Resign",
+      timestamp: now + 1000
+    }
+  })
+  t.equal(resign_result.Err, undefined)
+
+  console.log("  Alice registers the game again, which does not count it twice")
+  const again_result = await alice.callSync('main', 'register_finished_game_unchecked', { game_address })
+  t.equal(again_result.Err, undefined)
+
+  const bob_stats = await alice.callSync('main', 'get_player_stats', { agent: bob.agentId })
+  t.deepEqual(bob_stats.Ok, { wins: 0, losses: 1, draws: 0, resignations: 1 })
+
+  console.log("  Games cannot be registered before they end")
+  const unfinished_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now + 2000
+  })
+  const early_result = await alice.callSync('main', 'register_finished_game_unchecked', {
+    game_address: unfinished_result.Ok
+  })
+  t.equal(early_result.Ok, undefined)
+  t.ok(rejectedWith(early_result, "Only a game that has ended can be registered as finished"))
+
+  const leaderboard = await alice.callSync('main', 'get_leaderboard', {})
+  console.log(leaderboard)
+  t.equal(leaderboard.Ok[0].agent, alice.agentId)
+  t.equal(leaderboard.Ok[1].agent, bob.agentId)
+
+  const ratings = await bob.callSync('main', 'get_ratings', { system: 'Glicko2' })
+  t.equal(ratings.Ok.length, 2)
+  t.ok(ratings.Ok.every(rating => rating.deviation < 350))
+})
+
+diorama.registerScenario("Proposals limit who can accept them and when", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const proposal_result = await alice.callSync('main', 'create_proposal', {
+    message: 'Connect Four, you go first',
+    expires_at: now + 60000,
+    invited: bob.agentId,
+    max_acceptances: 1,
+    settings: { ruleset: 'ConnectFour', acceptor_moves_first: true }
+  })
+  t.equal(proposal_result.Err, undefined)
+  const proposal_addr = proposal_result.Ok
+
+  console.log("  Alice cannot accept her own invitation to Bob")
+  const self_accept_result = await alice.callSync('main', 'accept_proposal', {
+    proposal_addr,
+    created_at: now
+  })
+  t.equal(self_accept_result.Ok, undefined)
+
+  console.log("  Bob cannot accept an expired proposal, even by backdating the game")
+  const expired_proposal_result = await alice.callSync('main', 'create_proposal', {
+    message: 'Too late',
+    expires_at: now - 30000
+  })
+  const expired_proposal_addr = expired_proposal_result.Ok
+  for (const created_at of [now, now - 40000]) {
+    const late_accept_result = await bob.callSync('main', 'accept_proposal', {
+      proposal_addr: expired_proposal_addr,
+      created_at
+    })
+    t.equal(late_accept_result.Ok, undefined)
+  }
+
+  const accept_result = await bob.callSync('main', 'accept_proposal', {
+    proposal_addr,
+    created_at: now
+  })
+  t.equal(accept_result.Err, undefined)
+  const game_address = accept_result.Ok
+
+  console.log("  The proposal only allows one game")
+  const second_accept_result = await bob.callSync('main', 'accept_proposal', {
+    proposal_addr,
+    created_at: now + 1000
+  })
+  t.equal(second_accept_result.Ok, undefined)
+
+  // Bob accepted, so he moves first in a game of Connect Four
+  const move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Drop: { column: 3 } },
+      timestamp: now + 3000
+    }
+  })
+  t.equal(move_result.Err, undefined)
+})
+
+diorama.registerScenario("Only the author can remove a proposal", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const proposal_result = await alice.callSync('main', 'create_proposal', {
+    message: 'Anyone for a game?'
+  })
+  const proposal_addr = proposal_result.Ok
+
+  const bob_remove_result = await bob.callSync('main', 'remove_proposal', { proposal_addr })
+  t.equal(bob_remove_result.Ok, undefined)
+
+  const remove_result = await alice.callSync('main', 'remove_proposal', { proposal_addr })
+  t.equal(remove_result.Err, undefined)
+
+  const proposals = await bob.callSync('main', 'get_proposals', {})
+  t.deepEqual(proposals.Ok, [])
+})
+
+diorama.registerScenario("Proposals are no longer listed once they are accepted", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const proposals = [
+    { message: 'One game only', max_acceptances: 1 },
+    { message: 'Anyone for a game?' },
+  ]
+  for (const proposal of proposals) {
+    const proposal_result = await alice.callSync('main', 'create_proposal', proposal)
+    const accept_result = await bob.callSync('main', 'accept_proposal', {
+      proposal_addr: proposal_result.Ok,
+      created_at: now
+    })
+    t.equal(accept_result.Err, undefined)
+  }
+
+  const listed_proposals = await alice.callSync('main', 'get_proposals', {})
+  t.deepEqual(listed_proposals.Ok, [])
+})
+
+diorama.registerScenario("Proposers are notified when their proposal is accepted", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const proposal_result = await alice.callSync('main', 'create_proposal', {
+    message: 'Anyone for a game?'
+  })
+  const proposal_addr = proposal_result.Ok
+
+  const accept_result = await bob.callSync('main', 'accept_proposal', {
+    proposal_addr,
+    created_at: now
+  })
+  const game_address = accept_result.Ok
+
+  const notifications = await alice.callSync('main', 'get_notifications', {})
+  t.deepEqual(notifications.Ok, [{
+    proposal: proposal_addr,
+    game: game_address,
+    accepted_by: bob.agentId
+  }])
+
+  console.log("  Notifications are only delivered once")
+  const seen_notifications = await alice.callSync('main', 'get_notifications', {})
+  t.deepEqual(seen_notifications.Ok, [])
+
+  const alice_games = await alice.callSync('main', 'list_my_games', {})
+  t.equal(alice_games.Ok[0].address, game_address)
+})
+
+diorama.registerScenario("Agents cannot make moves in other agents' games", async (s, t, {alice, bob, carol}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now
+  })
+  const game_address = create_game_result.Ok
+
+  console.log("  Carol tries to move for Bob")
+  const spoofed_move_result = await carol.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 0, y: 0 } },
+      timestamp: now + 1000
+    }
+  })
+  t.equal(spoofed_move_result.Ok, undefined)
+
+  console.log("  Carol tries to offer a draw, which can be done out of turn")
+  const spoofed_offer_result = await carol.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: "OfferDraw",
+      timestamp: now + 1000
+    }
+  })
+  t.equal(spoofed_offer_result.Ok, undefined)
+
+  const forged_move = {
+    game: game_address,
+    author: bob.agentId,
+    move_type: { Place: { x: 0, y: 0 } },
+    previous_move: game_address,
+    sequence: 0,
+    timestamp: now + 1000
+  }
+  console.log("  Carol commits a move authored by Bob")
+  const forged_result = await carol.callSync('main', 'commit_move_unchecked', { new_move: forged_move })
+  t.equal(forged_result.Ok, undefined)
+  t.ok(rejectedWith(forged_result, "Cannot author a move from another agent"))
+
+  console.log("  Alice commits a move authored by Bob, her opponent")
+  const opponent_result = await alice.callSync('main', 'commit_move_unchecked', { new_move: forged_move })
+  t.equal(opponent_result.Ok, undefined)
+  t.ok(rejectedWith(opponent_result, "Cannot author a move from another agent"))
+
+  const move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 0, y: 0 } },
+      timestamp: now + 2000
+    }
+  })
+  t.equal(move_result.Err, undefined)
+
+  const game_state = await alice.callSync('main', 'get_state', { game_address })
+  t.equal(game_state.Ok.moves.length, 1)
+  t.equal(game_state.Ok.moves[0].author, bob.agentId)
+})
+
+diorama.registerScenario("Games cannot be forked and forks that get through are reported", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now
+  })
+  const game_address = create_game_result.Ok
+
+  console.log("  Bob commits a move that is not linked yet, as if it were still on its way")
+  const first_move = {
+    game: game_address,
+    author: bob.agentId,
+    move_type: { Place: { x: 0, y: 0 } },
+    previous_move: game_address,
+    sequence: 0,
+    timestamp: now + 1000
+  }
+  const first_result = await bob.callSync('main', 'commit_move_unchecked', { new_move: first_move })
+  t.equal(first_result.Err, undefined)
+  const first_address = first_result.Ok
+
+  const move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 1, y: 1 } },
+      timestamp: now + 2000
+    }
+  })
+  t.equal(move_result.Err, undefined)
+
+  console.log("  The first move's links arrive, forking the game")
+  for (const [link_type, tag] of [["", ""], ["move_log", "0000000000"]]) {
+    const link_result = await bob.callSync('main', 'link_unchecked', {
+      base: game_address,
+      target: first_address,
+      link_type,
+      tag
+    })
+    t.equal(link_result.Err, undefined)
+  }
+
+  const integrity_result = await alice.callSync('main', 'get_game_integrity', { game_address })
+  console.log(integrity_result)
+  t.equal(integrity_result.Ok.is_intact, false)
+  t.equal(integrity_result.Ok.forks.length, 1)
+  t.equal(integrity_result.Ok.forks[0].previous_move, game_address)
+  t.equal(integrity_result.Ok.forks[0].moves.length, 2)
+  t.ok(integrity_result.Ok.forks[0].moves.includes(first_address))
+
+  console.log("  Both players follow the same branch")
+  const alice_state = await alice.callSync('main', 'get_state', { game_address })
+  const bob_state = await bob.callSync('main', 'get_state', { game_address })
+  t.equal(alice_state.Ok.moves.length, 1)
+  t.deepEqual(alice_state.Ok, bob_state.Ok)
+
+  console.log("  Once Alice has replied Bob cannot go back and change his move")
+  const reply_result = await alice.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 2, y: 2 } },
+      timestamp: now + 3000
+    }
+  })
+  t.equal(reply_result.Err, undefined)
+  const rewritten_move = Object.assign({}, first_move, {
+    move_type: { Place: { x: 2, y: 0 } },
+    timestamp: now + 2500
+  })
+  const rewrite_result = await bob.callSync('main', 'commit_move_unchecked', { new_move: rewritten_move })
+  t.equal(rewrite_result.Ok, undefined)
+  t.ok(rejectedWith(rewrite_result, "Another move has already been made after the previous move"))
+
+  const final_state = await bob.callSync('main', 'get_state', { game_address })
+  t.equal(final_state.Ok.moves.length, 2)
+  t.deepEqual(final_state.Ok.moves[0], alice_state.Ok.moves[0])
+})
+
+diorama.registerScenario("Moves cannot be linked into another game's history", async (s, t, {alice, bob, carol}) => {
+  const now = Date.now()
+  const game_1_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now
+  })
+  const game_1 = game_1_result.Ok
+  const game_2_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now + 1
+  })
+  const game_2 = game_2_result.Ok
+
+  console.log("  Bob commits a move in the second game without linking it")
+  const move_result = await bob.callSync('main', 'commit_move_unchecked', {
+    new_move: {
+      game: game_2,
+      author: bob.agentId,
+      move_type: { Place: { x: 0, y: 0 } },
+      previous_move: game_2,
+      sequence: 0,
+      timestamp: now + 1000
+    }
+  })
+  t.equal(move_result.Err, undefined)
+  const move_address = move_result.Ok
+  const link = (agent, base, link_type, tag) => agent.callSync('main', 'link_unchecked', {
+    base,
+    target: move_address,
+    link_type,
+    tag
+  })
+
+  console.log("  Bob tries to graft it onto the first game")
+  const graft_result = await link(bob, game_1, "", "")
+  t.equal(graft_result.Ok, undefined)
+  t.ok(rejectedWith(graft_result, "A move can only be linked from the move it follows"))
+  const log_result = await link(bob, game_1, "move_log", "0000000000")
+  t.equal(log_result.Ok, undefined)
+  t.ok(rejectedWith(log_result, "A move can only be logged in its own game"))
+
+  console.log("  Other agents try to link Bob's move into its own game")
+  for (const agent of [carol, alice]) {
+    const foreign_result = await link(agent, game_2, "", "")
+    t.equal(foreign_result.Ok, undefined)
+    t.ok(rejectedWith(foreign_result, "Only the author of a move can link to it"))
+  }
+
+  console.log("  Bob links it where it belongs")
+  for (const [link_type, tag] of [["", ""], ["move_log", "0000000000"]]) {
+    const link_result = await link(bob, game_2, link_type, tag)
+    t.equal(link_result.Err, undefined)
+  }
+
+  const game_1_state = await alice.callSync('main', 'get_state', { game_address: game_1 })
+  t.equal(game_1_state.Ok.moves.length, 0)
+  const game_2_state = await alice.callSync('main', 'get_state', { game_address: game_2 })
+  t.equal(game_2_state.Ok.moves.length, 1)
+  const integrity_result = await alice.callSync('main', 'get_game_integrity', { game_address: game_1 })
+  t.equal(integrity_result.Ok.is_intact, true)
+})
+
+diorama.registerScenario("Games must be dated when they are created", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  for (const timestamp of [now + 60 * 60 * 1000, 0]) {
+    const create_game_result = await alice.callSync('main', 'create_game', {
+      opponent: bob.agentId,
+      timestamp
+    })
+    t.equal(create_game_result.Ok, undefined)
+  }
+
+  console.log("  Games started from a proposal record it")
+  const proposal_result = await alice.callSync('main', 'create_proposal', {
+    message: 'Anyone for a game?'
+  })
+  const proposal_addr = proposal_result.Ok
+  const accept_result = await bob.callSync('main', 'accept_proposal', {
+    proposal_addr,
+    created_at: now
+  })
+  t.equal(accept_result.Err, undefined)
+  const responses = await alice.callSync('main', 'check_responses', { proposal_addr })
+  t.equal(responses.Ok[0].entry.proposal, proposal_addr)
+  t.equal(responses.Ok[0].entry.player_2, alice.agentId)
+})
+
+diorama.registerScenario("Players can answer a draw offer out of turn", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now
+  })
+  const game_address = create_game_result.Ok
+
+  const moves = [
+    [bob, { Place: { x: 0, y: 0 } }],
+    [bob, "OfferDraw"],
+    [alice, "AcceptDraw"],
+  ]
+  for (let i = 0; i < moves.length; i++) {
+    const [player, move_type] = moves[i]
+    const move_result = await player.callSync('main', 'make_move', {
+      new_move: {
+        game: game_address,
+        move_type,
+        timestamp: now + i + 1
+      }
+    })
+    t.equal(move_result.Err, undefined)
+  }
+
+  const game_state = await bob.callSync('main', 'get_state', { game_address })
+  t.equal(game_state.Ok.outcome, 'Drawn')
+})
+
+diorama.registerScenario("Moves must be dated when they are made", async (s, t, {alice, bob}) => {
+  const now = Date.now()
+  const create_game_result = await alice.callSync('main', 'create_game', {
+    opponent: bob.agentId,
+    timestamp: now,
+    time_control: { PerMove: { seconds: 30 } }
+  })
+  const game_address = create_game_result.Ok
+
+  const bad_moves = [
+    ["  Bob dates his move before the game was created", now - 1000],
+    ["  Bob dates his move a day from now", now + 24 * 60 * 60 * 1000],
+  ]
+  for (const [description, timestamp] of bad_moves) {
+    console.log(description)
+    const move_result = await bob.callSync('main', 'make_move', {
+      new_move: {
+        game: game_address,
+        move_type: { Place: { x: 0, y: 0 } },
+        timestamp
+      }
+    })
+    t.equal(move_result.Ok, undefined)
+  }
+
+  const move_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 0, y: 0 } },
+      timestamp: now + 2000
+    }
+  })
+  t.equal(move_result.Err, undefined)
+
+  console.log("  Alice dates her move before Bob's")
+  const early_move_result = await alice.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: { Place: { x: 1, y: 1 } },
+      timestamp: now + 1000
+    }
+  })
+  t.equal(early_move_result.Ok, undefined)
+
+  console.log("  Bob claims a timeout by dating his claim an hour from now")
+  const claim_result = await bob.callSync('main', 'make_move', {
+    new_move: {
+      game: game_address,
+      move_type: "ClaimTimeout",
+      timestamp: now + 60 * 60 * 1000
+    }
+  })
+  t.equal(claim_result.Ok, undefined)
+
+  const game_state = await alice.callSync('main', 'get_state', { game_address })
+  t.equal(game_state.Ok.moves.length, 1)
+  t.equal(game_state.Ok.outcome, 'InProgress')
+})
+
 diorama.run()
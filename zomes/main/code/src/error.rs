@@ -0,0 +1,8 @@
+use game_rules::GameError;
+use hdk::error::ZomeApiError;
+
+/// Hands a `GameError` back to the caller as JSON inside `ZomeApiError::Internal`,
+/// so the CLI and UI can read back exactly what went wrong
+pub fn to_zome_error(error: GameError) -> ZomeApiError {
+    ZomeApiError::Internal(serde_json::to_string(&error).unwrap_or_else(|_| error.to_string()))
+}
@@ -1,152 +1,325 @@
+use game_rules::{GameError, GameRules, Ruleset, TimeControl};
 use hdk::{
     entry_definition::ValidatingEntryType,
-    error::{ZomeApiError, ZomeApiResult},
+    error::ZomeApiResult,
     holochain_core_types::{
-        dna::entry_types::Sharing, entry::Entry, link::LinkMatch, validation::EntryValidationData,
+        dna::entry_types::Sharing, entry::Entry, link::LinkMatch, time::Iso8601,
+        validation::EntryValidationData,
     },
     holochain_json_api::{error::JsonError, json::JsonString},
     holochain_persistence_api::cas::content::{Address, AddressableContent},
-    utils,
+    LinkValidationData, AGENT_ADDRESS,
 };
 use std::convert::TryFrom;
 
+use crate::error::to_zome_error;
 use crate::game_move::Move;
-use crate::GameState;
+use crate::matchmaking::{get_acceptances, is_valid_acceptance, GameProposal};
+use crate::notifications;
 
-#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
 pub struct Game {
     pub player_1: Address,
     pub player_2: Address,
-    pub created_at: u32,
+    /// Milliseconds since the Unix epoch, as are all timestamps
+    pub created_at: u64,
+    // games from before there was more than one ruleset are tic-tac-toe
+    #[serde(default)]
+    pub ruleset: Ruleset,
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,
+    pub time_control: Option<TimeControl>,
+    #[serde(default)]
+    pub player_1_moves_first: bool,
+    /// The proposal the game was started from, when it was not created directly
+    #[serde(default)]
+    pub proposal: Option<Address>,
+}
+
+impl<'a> From<&'a Game> for game_rules::Game {
+    fn from(game: &Game) -> Self {
+        game_rules::Game {
+            player_1: game.player_1.to_string(),
+            player_2: game.player_2.to_string(),
+            created_at: game.created_at,
+            width: game.width,
+            height: game.height,
+            win_length: game.win_length,
+            time_control: game.time_control,
+            player_1_moves_first: game.player_1_moves_first,
+        }
+    }
 }
 
 /*=====================================
 =            DHT Functions            =
 =====================================*/
 
-/// Traverse the linked list rooted at a game to find all the moves
-pub fn get_moves(game_address: &Address) -> ZomeApiResult<Vec<Move>> {
-    match hdk::get_links(game_address, LinkMatch::Any, LinkMatch::Any)?
+/// Games are linked from the agent address of each of their players with this type
+pub const PLAYER_GAMES_LINK: &str = "player_games";
+
+/// Commit a new game and link it from both players so each of them can find it again
+pub fn commit_game(game: Game) -> ZomeApiResult<Address> {
+    let players = [game.player_1.clone(), game.player_2.clone()];
+    let game_entry = Entry::App("game".into(), game.into());
+    let game_address = hdk::commit_entry(&game_entry)?;
+    for player in players.iter() {
+        hdk::link_entries(player, &game_address, PLAYER_GAMES_LINK, "")?;
+    }
+    Ok(game_address)
+}
+
+/// How a game stands from the point of view of one of its players
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum GameStatus {
+    MyTurn,
+    TheirTurn,
+    Won,
+    Lost,
+    Drawn,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameSummary {
+    pub address: Address,
+    pub opponent: Address,
+    pub status: GameStatus,
+    /// When the last move was made, or the game was created if there are no moves yet
+    pub last_activity: u64,
+}
+
+/// Summarise every game this agent plays in, most recently active first
+pub fn list_my_games() -> ZomeApiResult<Vec<GameSummary>> {
+    let me = Address::from(AGENT_ADDRESS.to_string());
+    let mut games = hdk::get_links(&me, LinkMatch::Exactly(PLAYER_GAMES_LINK), LinkMatch::Any)?
         .addresses()
         .into_iter()
-        .next()
+        // skip a game that fails to load instead of failing the whole list
+        .filter_map(|game_address| summarise(&game_address, &me).ok())
+        .collect::<Vec<GameSummary>>();
+    games.sort_by_key(|summary| std::cmp::Reverse(summary.last_activity));
+    Ok(games)
+}
+
+fn summarise(game_address: &Address, me: &Address) -> ZomeApiResult<GameSummary> {
+    let game = get_game(game_address)?;
+    let moves = get_moves(game_address)?;
+    let status = with_rules!(game.ruleset, R => {
+        let rules_game = game_rules::Game::from(&game);
+        let state = state_from_moves::<R>(&game, &moves)?;
+        let outcome = R::outcome(&state);
+        match outcome.winner() {
+            Some(winner) if winner == me.to_string() => GameStatus::Won,
+            Some(_) => GameStatus::Lost,
+            None if outcome.is_over() => GameStatus::Drawn,
+            None if R::next_player(&rules_game, &state) == me.to_string() => GameStatus::MyTurn,
+            None => GameStatus::TheirTurn,
+        }
+    });
+    Ok(GameSummary {
+        address: game_address.clone(),
+        opponent: if game.player_1 == *me {
+            game.player_2.clone()
+        } else {
+            game.player_1.clone()
+        },
+        status,
+        last_activity: moves
+            .last()
+            .map(|game_move| game_move.timestamp)
+            .unwrap_or(game.created_at),
+    })
+}
+
+/// Moves are linked from their game with this type and tagged with `move_log_tag`
+pub const MOVE_LOG_LINK: &str = "move_log";
+
+/// Zero pads a move's sequence number so the tags sort in the order the moves were made
+pub fn move_log_tag(sequence: u32) -> String {
+    format!("{:010}", sequence)
+}
+
+/// Load every move in a game from its move log with a single `get_links_and_load`, along with
+/// their addresses
+pub fn get_move_log(game_address: &Address) -> ZomeApiResult<Vec<(Address, Move)>> {
+    hdk::get_links_and_load(
+        game_address,
+        LinkMatch::Exactly(MOVE_LOG_LINK),
+        LinkMatch::Any,
+    )?
+    .into_iter()
+    .map(|result| {
+        let entry = result?;
+        let address = entry.address();
+        entry_as::<Move>(&address, entry, "move").map(|game_move| (address, game_move))
+    })
+    .collect()
+}
+
+/// Load every move in a game, oldest first. Where the game forked, the move with the lowest
+/// address is followed, so every agent settles on the same history and moves on the other
+/// branches are ignored. Players choose what their moves say, dates included, but not which
+/// address they end up at.
+pub fn get_moves(game_address: &Address) -> ZomeApiResult<Vec<Move>> {
+    let move_log = get_move_log(game_address)?;
+    let mut moves = Vec::new();
+    let mut previous_address = game_address.clone();
+    while let Some((address, next_move)) = move_log
+        .iter()
+        .filter(|(_, game_move)| game_move.previous_move == previous_address)
+        .min_by_key(|(address, _)| address.to_string())
     {
-        Some(first_move) => {
-            let mut move_addresses = vec![first_move];
-            let mut more = true;
-            while more {
-                more = match hdk::get_links(
-                    move_addresses.last().unwrap(),
-                    LinkMatch::Any,
-                    LinkMatch::Any,
-                )?
-                .addresses()
-                .into_iter()
-                .next()
-                {
-                    Some(addr) => {
-                        move_addresses.push(addr.clone());
-                        true
-                    }
-                    None => false,
-                }
-            }
-            let moves: Vec<Move> = move_addresses
-                .iter()
-                .map(|addr| {
-                    let move_entry = hdk::get_entry(addr).unwrap().unwrap();
-                    if let Entry::App(_, move_struct) = move_entry {
-                        Move::try_from(move_struct)
-                            .expect("Entry at address is type other than Move")
-                    } else {
-                        panic!("Not an app entry!")
-                    }
-                })
-                .collect();
-            Ok(moves)
+        previous_address = address.clone();
+        moves.push(next_move.clone());
+    }
+    Ok(moves)
+}
+
+/// Two or more moves that were all made in reply to the same previous move
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct Fork {
+    pub previous_move: Address,
+    /// In the order `get_moves` prefers them, so the first is the branch the game follows
+    pub moves: Vec<Address>,
+}
+
+/// Everything wrong with the chain of moves in a game. A game with no forks or orphans is intact.
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameIntegrity {
+    pub is_intact: bool,
+    pub forks: Vec<Fork>,
+    /// Moves whose previous move is neither the game nor another move in it
+    pub orphaned_moves: Vec<Address>,
+}
+
+/// Check the moves in a game form a single unbroken chain back to the game
+pub fn get_integrity(game_address: &Address) -> ZomeApiResult<GameIntegrity> {
+    let mut move_log = get_move_log(game_address)?;
+    move_log.sort_by_key(|(address, game_move)| (game_move.sequence, address.to_string()));
+
+    let mut forks: Vec<Fork> = Vec::new();
+    for (address, game_move) in move_log.iter() {
+        match forks
+            .iter_mut()
+            .find(|fork| fork.previous_move == game_move.previous_move)
+        {
+            Some(fork) => fork.moves.push(address.clone()),
+            None => forks.push(Fork {
+                previous_move: game_move.previous_move.clone(),
+                moves: vec![address.clone()],
+            }),
         }
-        None => Ok(Vec::new()),
     }
+    forks.retain(|fork| fork.moves.len() > 1);
+
+    let orphaned_moves: Vec<Address> = move_log
+        .iter()
+        .filter(|(_, game_move)| {
+            game_move.previous_move != *game_address
+                && !move_log
+                    .iter()
+                    .any(|(address, _)| *address == game_move.previous_move)
+        })
+        .map(|(address, _)| address.clone())
+        .collect();
+
+    Ok(GameIntegrity {
+        is_intact: forks.is_empty() && orphaned_moves.is_empty(),
+        forks,
+        orphaned_moves,
+    })
 }
 
-pub fn get_state(game_address: &Address) -> ZomeApiResult<GameState> {
+pub fn get_state<R: GameRules>(game_address: &Address) -> ZomeApiResult<R::State> {
     let moves = get_moves(game_address)?;
     let game = get_game(game_address)?;
-    let mut num_moves = moves.len();
-    let new_state = moves.iter().fold(GameState::initial(), |state, new_move| {
-        num_moves -= 1;
-        state.evolve(game.clone(), new_move, num_moves == 0)
-    });
-    Ok(new_state)
+    state_from_moves::<R>(&game, &moves)
 }
 
 pub fn get_game(game_address: &Address) -> ZomeApiResult<Game> {
-    utils::get_as_type(game_address.to_owned())
+    get_as(game_address, "game")
 }
 
-/*=====  End of DHT Functions  ======*/
-
-/*=============================================
-=            Local chain functions            =
-=============================================*/
+/// Load the app entry at an address, telling a missing entry apart from one of the wrong type
+pub fn get_as<T: TryFrom<JsonString>>(address: &Address, entry_type: &str) -> ZomeApiResult<T> {
+    match hdk::get_entry(address)? {
+        Some(entry) => entry_as(address, entry, entry_type),
+        None => Err(to_zome_error(GameError::NotFound {
+            address: address.to_string(),
+        })),
+    }
+}
 
-pub fn get_game_local_chain(
-    local_chain: Vec<Entry>,
-    game_address: &Address,
-) -> ZomeApiResult<Game> {
-    local_chain
-        .iter()
-        .filter(|entry| entry.address() == game_address.to_owned())
-        .filter_map(|entry| {
-            if let Entry::App(_, entry_data) = entry {
-                Some(Game::try_from(entry_data.clone()).unwrap())
-            } else {
-                None
-            }
+/// Read an entry that was loaded from `address` as an app entry of `entry_type`
+fn entry_as<T: TryFrom<JsonString>>(
+    address: &Address,
+    entry: Entry,
+    entry_type: &str,
+) -> ZomeApiResult<T> {
+    let wrong_type = || {
+        to_zome_error(GameError::WrongEntryType {
+            address: address.to_string(),
+            expected: entry_type.into(),
         })
-        .next()
-        .ok_or(ZomeApiError::HashNotFound)
+    };
+    match entry {
+        Entry::App(_, entry_data) => T::try_from(entry_data).map_err(|_| wrong_type()),
+        _ => Err(wrong_type()),
+    }
 }
 
-pub fn get_moves_local_chain(
-    local_chain: Vec<Entry>,
-    game_address: &Address,
-) -> ZomeApiResult<Vec<Move>> {
-    Ok(local_chain
-        .iter()
-        .filter_map(|entry| {
-            if let Entry::App(entry_type, entry_data) = entry {
-                if entry_type.to_string() == "move" {
-                    Some(Move::try_from(entry_data.clone()).unwrap())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
-        .filter(|game_move| game_move.game == game_address.to_owned())
-        .rev()
-        .collect())
-}
-
-pub fn get_state_local_chain(
-    local_chain: Vec<Entry>,
-    game_address: &Address,
-) -> ZomeApiResult<GameState> {
-    let moves = get_moves_local_chain(local_chain.clone(), game_address)?;
-    let game = get_game_local_chain(local_chain, game_address)?;
-    let mut num_moves = moves.len();
-    let new_state = moves
-        .iter()
-        .fold(GameState::initial(), move |state, new_move| {
-            num_moves -= 1;
-            state.evolve(game.clone(), new_move, num_moves == 0)
-        });
-    Ok(new_state)
+/// Load the moves that came before `game_move`, oldest first, by following each move back to the
+/// one it was made after until the game is reached
+pub fn get_previous_moves(game_move: &Move) -> ZomeApiResult<Vec<Move>> {
+    let mut moves = Vec::new();
+    let mut previous_address = game_move.previous_move.clone();
+    while previous_address != game_move.game {
+        let previous_move: Move = get_as(&previous_address, "move")?;
+        if previous_move.game != game_move.game {
+            return Err(to_zome_error(GameError::CorruptMoveChain {
+                reason: format!("move {} is from another game", previous_address),
+            }));
+        }
+        previous_address = previous_move.previous_move.clone();
+        moves.push(previous_move);
+    }
+    moves.reverse();
+    Ok(moves)
+}
+
+/// Replays the moves of a game, in order, to find where it stands now
+pub fn state_from_moves<R: GameRules>(game: &Game, moves: &[Move]) -> ZomeApiResult<R::State> {
+    let game = game_rules::Game::from(game);
+    moves.iter().try_fold(R::initial(&game), |state, new_move| {
+        let player_move = new_move
+            .player_move::<R>()
+            .map_err(|reason| to_zome_error(GameError::CorruptMoveChain { reason }))?;
+        Ok(R::evolve(&game, &state, &player_move))
+    })
 }
 
-/*=====  End of Local chain functions  ======*/
+/*=====  End of DHT Functions  ======*/
+
+/// How far the date an agent puts on an entry can be from the time it was committed, as agents'
+/// clocks disagree
+pub const CLOCK_DRIFT_MS: u64 = 60 * 1000;
+
+/// Agents date their own entries, so hold a `timestamp` to within `CLOCK_DRIFT_MS` of the time
+/// the entry's header says it was committed
+pub fn is_dated_near(timestamp: u64, committed_at: &Iso8601, what: &str) -> Result<(), String> {
+    let in_seconds = |millis: u64| Iso8601::from((millis / 1000) as i64);
+    if in_seconds(timestamp.saturating_sub(CLOCK_DRIFT_MS)) > *committed_at {
+        Err(format!("A {} cannot be dated after it was committed", what))
+    } else if in_seconds(timestamp.saturating_add(CLOCK_DRIFT_MS)) < *committed_at {
+        Err(format!(
+            "A {} cannot be dated long before it was committed",
+            what
+        ))
+    } else {
+        Ok(())
+    }
+}
 
 pub fn definition() -> ValidatingEntryType {
     entry!(
@@ -159,17 +332,69 @@ pub fn definition() -> ValidatingEntryType {
 
         validation: | validation_data: hdk::EntryValidationData<Game>| {
             match validation_data {
-                EntryValidationData::Create{entry, validation_data: _} => {
+                EntryValidationData::Create{entry, validation_data} => {
                     let game = entry as Game;
                     if game.player_1 == game.player_2 {
                         return Err("Player 1 and Player 2 must be different agents.".into())
                     }
-                    Ok(())
+                    if !validation_data.sources().contains(&game.player_1) {
+                        return Err("A game can only be created by its first player".into())
+                    }
+                    let committed_at = validation_data.package.chain_header.timestamp();
+                    is_dated_near(game.created_at, committed_at, "game")?;
+                    if let Some(proposal_address) = &game.proposal {
+                        let proposal: GameProposal = get_as(proposal_address, "game_proposal")
+                            .map_err(|_| "Could not load the proposal during validation")?;
+                        if proposal.agent != game.player_2 {
+                            return Err("A game started from a proposal must be played against its author".into())
+                        }
+                        let accepted = get_acceptances(proposal_address)
+                            .map_err(|_| "Could not load the games already started during validation")?;
+                        is_valid_acceptance(proposal_address, &proposal, &game, &accepted, committed_at)?;
+                    }
+                    with_rules!(game.ruleset, R => R::is_valid_game(&game_rules::Game::from(&game)))
                 },
                 _ => {
                     Err("Cannot modify or delete a game".into())
                 }
             }
-        }
+        },
+
+        links: [
+            from!(
+                "%agent_id",
+                link_type: PLAYER_GAMES_LINK,
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        // a game can only be listed for the agents playing it
+                        LinkValidationData::LinkAdd{link, ..} => {
+                            let game = get_game(link.link.target())
+                                .map_err(|_| "Could not load the game during validation")?;
+                            if *link.link.base() == game.player_1 || *link.link.base() == game.player_2 {
+                                Ok(())
+                            } else {
+                                Err("A game can only be linked from its players".into())
+                            }
+                        }
+                        LinkValidationData::LinkRemove{..} => {
+                            Err("Cannot remove a game from a player".into())
+                        }
+                    }
+                }
+            ),
+            from!(
+                "%agent_id",
+                link_type: notifications::NOTIFICATION_LINK,
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData| {
+                    notifications::is_valid_notification(validation_data)
+                }
+            )
+        ]
     )
 }
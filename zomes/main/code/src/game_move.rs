@@ -1,29 +1,53 @@
 use hdk::{
     entry_definition::ValidatingEntryType,
     holochain_core_types::{
-        dna::entry_types::Sharing, entry::Entry, validation::EntryValidationData,
+        dna::entry_types::Sharing,
+        entry::Entry,
+        link::LinkMatch,
+        validation::{EntryValidationData, ValidationData},
     },
     holochain_json_api::{error::JsonError, json::JsonString},
-    holochain_persistence_api::cas::content::Address,
+    holochain_persistence_api::cas::content::{Address, AddressableContent},
+    LinkValidationData,
 };
 
-use crate::game::{get_game_local_chain, get_state_local_chain};
-use crate::MoveType;
+use game_rules::{GameRules, PlayerMove};
+
+use crate::game::{
+    get_as, get_game, get_previous_moves, is_dated_near, move_log_tag, state_from_moves, Game,
+    MOVE_LOG_LINK,
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
 pub struct MoveInput {
     pub game: Address,
-    pub move_type: MoveType,
-    pub timestamp: u32,
+    pub move_type: serde_json::Value,
+    pub timestamp: u64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
 pub struct Move {
     pub game: Address,
     pub author: Address,
-    pub move_type: MoveType,
+    pub move_type: serde_json::Value,
     pub previous_move: Address,
-    pub timestamp: u32,
+    /// How many moves were made in the game before this one
+    pub sequence: u32,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl Move {
+    /// Reads the move back as a move in the game being played
+    pub fn player_move<R: GameRules>(&self) -> Result<PlayerMove<R::Move>, String> {
+        serde_json::from_value(self.move_type.clone())
+            .map(|move_type| PlayerMove {
+                author: self.author.to_string(),
+                move_type,
+                timestamp: self.timestamp,
+            })
+            .map_err(|e| format!("Not a valid move: {}", e))
+    }
 }
 
 pub fn definition() -> ValidatingEntryType {
@@ -32,29 +56,44 @@ pub fn definition() -> ValidatingEntryType {
         description: "A move by an agent in an game",
         sharing: Sharing::Public,
         validation_package: || {
-            hdk::ValidationPackageDefinition::ChainFull
+            hdk::ValidationPackageDefinition::Entry
         },
 
         validation: | validation_data: hdk::EntryValidationData<Move>| {
             match validation_data {
                 EntryValidationData::Create{entry, validation_data} => {
-                    let mut local_chain = validation_data.package.source_chain_entries
-                        .ok_or("Could not retrieve source chain")?;
-                    hdk::debug(format!("{:?}", local_chain))?;
-
-                    // load the game and game state
                     let _new_move = Move::from(entry);
+                    // a move can only be made by the agent committing it
+                    if !validation_data.sources().contains(&_new_move.author) {
+                        return Err("Cannot author a move from another agent".into())
+                    }
 
-                    // Sometimes the validating entry is already in the chain when validation runs,
-                    // To make our state reduction work correctly this must be removed
-                    local_chain.remove_item(&Entry::App("move".into() , _new_move.clone().into()));
-
-                    let state = get_state_local_chain(local_chain.clone(), &_new_move.game)
-                        .map_err(|_| "Could not load state during validation")?;
-                    let game = get_game_local_chain(local_chain, &_new_move.game)
+                    // the game and the moves before this one are loaded from the DHT, so players
+                    // only ever commit their own moves
+                    let game = get_game(&_new_move.game)
                         .map_err(|_| "Could not load game during validation")?;
+                    if _new_move.author != game.player_1 && _new_move.author != game.player_2 {
+                        return Err("Only the players of a game can make moves in it".into())
+                    }
+                    let previous_moves = get_previous_moves(&_new_move)
+                        .map_err(|_| "Could not load moves during validation")?;
+                    if _new_move.sequence as usize != previous_moves.len() {
+                        return Err("Move is not numbered in the order it was made".into())
+                    }
+                    is_in_order(&_new_move, &game, &previous_moves)?;
+                    // a second move following the same one would fork the game
+                    is_only_successor(&_new_move)?;
+                    // the clock is only as good as the time moves are dated with
+                    let committed_at = validation_data.package.chain_header.timestamp();
+                    is_dated_near(_new_move.timestamp, committed_at, "move")?;
 
-                    _new_move.is_valid(game, state)
+                    // the game decides which rules the move is validated against
+                    with_rules!(game.ruleset, R => {
+                        let state = state_from_moves::<R>(&game, &previous_moves)
+                            .map_err(|_| "Could not load state during validation")?;
+                        let player_move = _new_move.player_move::<R>()?;
+                        R::is_valid(&game_rules::Game::from(&game), &state, &player_move)
+                    })
                 },
                 _ => {
                     Err("Cannot modify or delete a move".into())
@@ -63,14 +102,38 @@ pub fn definition() -> ValidatingEntryType {
         },
 
         links: [
+            from!(
+                "game",
+                link_type: MOVE_LOG_LINK,
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        LinkValidationData::LinkAdd{link, validation_data} => {
+                            let new_move = get_linked_move(link.link.target(), &validation_data)?;
+                            if new_move.game != *link.link.base() {
+                                return Err("A move can only be logged in its own game".into())
+                            }
+                            if *link.link.tag() != move_log_tag(new_move.sequence) {
+                                return Err("A move must be logged in the order it was made".into())
+                            }
+                            Ok(())
+                        }
+                        LinkValidationData::LinkRemove{..} => {
+                            Err("Cannot remove a move from a game".into())
+                        }
+                    }
+                }
+            ),
             from!(
                 "game",
                 link_type: "",
                 validation_package: || {
                     hdk::ValidationPackageDefinition::Entry
                 },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    is_successor(validation_data, |game_address| Ok(game_address.clone()))
                 }
             ),
             from!(
@@ -79,10 +142,82 @@ pub fn definition() -> ValidatingEntryType {
                 validation_package: || {
                     hdk::ValidationPackageDefinition::Entry
                 },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    is_successor(validation_data, |previous_move| {
+                        get_as::<Move>(previous_move, "move")
+                            .map(|previous_move| previous_move.game)
+                            .map_err(|_| "Could not load the previous move during validation".into())
+                    })
                 }
             )
         ]
     )
 }
+
+/// A move cannot be made before the game was created or before the move it follows
+fn is_in_order(new_move: &Move, game: &Game, previous_moves: &[Move]) -> Result<(), String> {
+    if new_move.timestamp < game.created_at {
+        return Err("Move is dated before the game was created".into());
+    }
+    match previous_moves.last() {
+        Some(previous_move) if new_move.timestamp < previous_move.timestamp => {
+            Err("Move is dated before the previous move".into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A game or move can only be followed by one move, so reject a move when another already follows
+/// the one it was made after. Two moves made at the same moment can both be committed before
+/// either is linked, `game::get_moves` settles which of those the game follows.
+fn is_only_successor(new_move: &Move) -> Result<(), String> {
+    let address = Entry::App("move".into(), new_move.clone().into()).address();
+    let successors = hdk::get_links(
+        &new_move.previous_move,
+        LinkMatch::Exactly(""),
+        LinkMatch::Any,
+    )
+    .map_err(|_| "Could not load the existing moves during validation")?
+    .addresses();
+    if successors.iter().any(|successor| *successor != address) {
+        Err("Another move has already been made after the previous move".into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Loads the move a link points to, which only the move's author can link
+fn get_linked_move(address: &Address, validation_data: &ValidationData) -> Result<Move, String> {
+    let linked_move: Move =
+        get_as(address, "move").map_err(|_| "Could not load the move during validation")?;
+    if validation_data.sources().contains(&linked_move.author) {
+        Ok(linked_move)
+    } else {
+        Err("Only the author of a move can link to it".into())
+    }
+}
+
+/// A move is linked from the game or move it follows, in the same game and by the move's author.
+/// `game_of` finds the game the link's base belongs to. Forks are rejected with the move itself,
+/// see `is_only_successor`, so links to moves that got through together are still accepted.
+fn is_successor<F>(validation_data: LinkValidationData, game_of: F) -> Result<(), String>
+where
+    F: Fn(&Address) -> Result<Address, String>,
+{
+    match validation_data {
+        LinkValidationData::LinkAdd {
+            link,
+            validation_data,
+        } => {
+            let new_move = get_linked_move(link.link.target(), &validation_data)?;
+            if new_move.previous_move != *link.link.base() {
+                return Err("A move can only be linked from the move it follows".into());
+            }
+            if new_move.game != game_of(link.link.base())? {
+                return Err("A move can only follow a move in the same game".into());
+            }
+            Ok(())
+        }
+        LinkValidationData::LinkRemove { .. } => Err("Cannot remove a move from a game".into()),
+    }
+}
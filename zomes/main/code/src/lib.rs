@@ -1,4 +1,4 @@
-#![feature(try_from, vec_remove_item, proc_macro_hygiene)]
+#![feature(try_from, proc_macro_hygiene)]
 #[macro_use]
 extern crate hdk;
 extern crate serde;
@@ -8,27 +8,38 @@ extern crate serde_json;
 #[macro_use]
 extern crate holochain_json_derive;
 
+#[macro_use]
+extern crate game_rules;
 extern crate hdk_proc_macros;
 use hdk_proc_macros::zome;
 
 use hdk::{
-    entry_definition::ValidatingEntryType, error::ZomeApiResult,
-    holochain_core_types::entry::Entry, holochain_persistence_api::cas::content::Address,
+    entry_definition::ValidatingEntryType,
+    error::ZomeApiResult,
+    holochain_core_types::entry::Entry,
+    holochain_json_api::json::{default_to_json, JsonString},
+    holochain_persistence_api::cas::content::{Address, AddressableContent},
     AGENT_ADDRESS,
 };
 
-// This is where you would import your own game State, MoveType and state_reducer
+// Every game in game_rules can be played, each Game entry records which one it is
 
-mod your_game;
-pub use your_game::{GameState, MoveType};
+use game_rules::{rating::RatingSystem, GameError, GameRules, PlayerMove, Ruleset, TimeControl};
 
+mod error;
 mod game;
 mod game_move;
 mod matchmaking;
+mod notifications;
+mod stats;
+mod test_hooks;
 
-use game::Game;
+use error::to_zome_error;
+use game::{Game, GameIntegrity, GameSummary};
 use game_move::{Move, MoveInput};
-use matchmaking::{GameProposal, GetResponse};
+use matchmaking::{GameProposal, GameSettings, GetResponse};
+use notifications::Notification;
+use stats::{LeaderboardEntry, PlayerStats};
 
 #[zome]
 pub mod main {
@@ -38,6 +49,11 @@ pub mod main {
         Ok(())
     }
 
+    #[receive]
+    pub fn receive(from: Address, msg_json: String) -> String {
+        notifications::receive(from, msg_json)
+    }
+
     /*=========================================
     =            Entry Definitions            =
     =========================================*/
@@ -69,61 +85,190 @@ pub mod main {
     ======================================*/
 
     #[zome_fn("hc_public")]
-    fn create_game(opponent: Address, timestamp: u32) -> ZomeApiResult<Address> {
-        let new_game = Game {
+    fn create_game(
+        opponent: Address,
+        timestamp: u64,
+        width: Option<usize>,
+        height: Option<usize>,
+        win_length: Option<usize>,
+        ruleset: Option<Ruleset>,
+        time_control: Option<TimeControl>,
+    ) -> ZomeApiResult<Address> {
+        let ruleset = ruleset.unwrap_or_default();
+        let new_game = with_rules!(ruleset, R => Game {
             player_1: AGENT_ADDRESS.to_string().into(),
             player_2: opponent,
             created_at: timestamp,
-        };
-        let game_entry = Entry::App("game".into(), new_game.into());
-        hdk::commit_entry(&game_entry)
+            ruleset,
+            width: width.unwrap_or(R::DEFAULT_WIDTH),
+            height: height.unwrap_or(R::DEFAULT_HEIGHT),
+            win_length: win_length.unwrap_or(R::DEFAULT_WIN_LENGTH),
+            time_control,
+            player_1_moves_first: false,
+            proposal: None,
+        });
+        game::commit_game(new_game)
     }
 
     #[zome_fn("hc_public")]
     fn make_move(new_move: MoveInput) -> ZomeApiResult<()> {
-        // get all the moves from the DHT by following the hash chain
+        // get all the moves from the DHT via the game's move log
+        let game = game::get_game(&new_move.game)?;
         let moves = game::get_moves(&new_move.game)?;
 
-        // commit the latest move to local chain to allow validation of the next move (if one exists)
+        // reject a move that breaks the rules before touching the chain
+        let ends_game = with_rules!(game.ruleset, R => {
+            let move_type = serde_json::from_value::<<R as GameRules>::Move>(
+                new_move.move_type.clone(),
+            )
+            .map_err(|e| {
+                to_zome_error(GameError::rule_violation(format!("Not a valid move: {}", e)))
+            })?;
+            let player_move = PlayerMove {
+                author: AGENT_ADDRESS.to_string(),
+                move_type,
+                timestamp: new_move.timestamp,
+            };
+            let rules_game = game_rules::Game::from(&game);
+            let state = game::state_from_moves::<R>(&game, &moves)?;
+            R::is_valid(&rules_game, &state, &player_move)
+                .map_err(|reason| to_zome_error(GameError::RuleViolation { reason }))?;
+            R::outcome(&R::evolve(&rules_game, &state, &player_move)).is_over()
+        });
+
+        // the new move follows the latest one, or the game itself when no moves have been made
         let base_address = match moves.last() {
-            Some(last_move) => {
-                let new_move_entry = Entry::App("move".into(), last_move.into());
-                hdk::commit_entry(&new_move_entry)?
-            }
-            None => {
-                // no moves have been made so commit the Game
-                let game = game::get_game(&new_move.game)?;
-                let game_entry = Entry::App("game".into(), game.into());
-                hdk::commit_entry(&game_entry)?
-            }
+            Some(last_move) => Entry::App("move".into(), last_move.into()).address(),
+            None => new_move.game.clone(),
         };
 
+        let sequence = moves.len() as u32;
         let new_move = Move {
             game: new_move.game,
             author: AGENT_ADDRESS.to_string().into(),
             move_type: new_move.move_type,
             previous_move: base_address.clone(),
+            sequence,
             timestamp: new_move.timestamp,
         };
+        let game_address = new_move.game.clone();
         let move_entry = Entry::App("move".into(), new_move.into());
         let move_address = hdk::commit_entry(&move_entry)?;
         hdk::link_entries(&base_address, &move_address, "", "")?;
+        // index the move on the game so the whole history can be loaded at once
+        hdk::link_entries(
+            &game_address,
+            &move_address,
+            game::MOVE_LOG_LINK,
+            &game::move_log_tag(sequence),
+        )?;
+        if ends_game {
+            stats::register_finished_game(&game_address)?;
+        }
         Ok(())
     }
 
+    // only does anything in a DNA built for the tests, see test_hooks
+    #[zome_fn("hc_public")]
+    fn commit_move_unchecked(new_move: Move) -> ZomeApiResult<Address> {
+        test_hooks::commit_move(new_move)
+    }
+
+    // only does anything in a DNA built for the tests, see test_hooks
+    #[zome_fn("hc_public")]
+    fn link_unchecked(
+        base: Address,
+        target: Address,
+        link_type: String,
+        tag: String,
+    ) -> ZomeApiResult<Address> {
+        test_hooks::link(&base, &target, &link_type, &tag)
+    }
+
+    // only does anything in a DNA built for the tests, see test_hooks
+    #[zome_fn("hc_public")]
+    fn register_finished_game_unchecked(game_address: Address) -> ZomeApiResult<()> {
+        test_hooks::register_finished_game(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_state(game_address: Address) -> ZomeApiResult<JsonString> {
+        let game = game::get_game(&game_address)?;
+        with_rules!(game.ruleset, R => {
+            Ok(default_to_json(game::get_state::<R>(&game_address)?))
+        })
+    }
+
+    #[zome_fn("hc_public")]
+    fn render_state(game_address: Address, spectator: Option<bool>) -> ZomeApiResult<String> {
+        let game = game::get_game(&game_address)?;
+        // spectators are shown whose turn it is by address rather than being told it is theirs
+        let viewer = if spectator.unwrap_or_default() {
+            None
+        } else {
+            Some(AGENT_ADDRESS.to_string())
+        };
+        with_rules!(game.ruleset, R => {
+            let rules_game = game_rules::Game::from(&game);
+            let state = game::get_state::<R>(&game_address)?;
+            Ok(R::render(&rules_game, &state, viewer.as_ref().map(String::as_str)))
+        })
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_best_move(game_address: Address) -> ZomeApiResult<JsonString> {
+        let game = game::get_game(&game_address)?;
+        with_rules!(game.ruleset, R => {
+            let rules_game = game_rules::Game::from(&game);
+            let state = game::get_state::<R>(&game_address)?;
+            if R::outcome(&state).is_over() {
+                return Err(to_zome_error(GameError::rule_violation("This game has ended")));
+            }
+            if R::next_player(&rules_game, &state) != AGENT_ADDRESS.to_string() {
+                return Err(to_zome_error(GameError::not_your_turn()));
+            }
+            R::best_move(&rules_game, &state)
+                .map(default_to_json)
+                .ok_or_else(|| {
+                    to_zome_error(GameError::rule_violation("There is no move to suggest"))
+                })
+        })
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_game_integrity(game_address: Address) -> ZomeApiResult<GameIntegrity> {
+        game::get_integrity(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn list_my_games() -> ZomeApiResult<Vec<GameSummary>> {
+        game::list_my_games()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_player_stats(agent: Address) -> ZomeApiResult<PlayerStats> {
+        stats::get_player_stats(&agent)
+    }
+
     #[zome_fn("hc_public")]
-    fn get_state(game_address: Address) -> ZomeApiResult<GameState> {
-        game::get_state(&game_address)
+    fn get_leaderboard() -> ZomeApiResult<Vec<LeaderboardEntry>> {
+        stats::get_leaderboard()
     }
 
     #[zome_fn("hc_public")]
-    fn render_state(game_address: Address) -> ZomeApiResult<String> {
-        Ok(game::get_state(&game_address)?.render())
+    fn get_ratings(system: Option<RatingSystem>) -> ZomeApiResult<JsonString> {
+        let results = stats::get_results()?;
+        Ok(default_to_json(game_rules::rating::ratings(
+            system.unwrap_or_default(),
+            &results,
+        )))
     }
 
     #[zome_fn("hc_public")]
-    fn get_valid_moves() -> ZomeApiResult<Vec<MoveType>> {
-        Ok(MoveType::describe())
+    fn get_valid_moves(ruleset: Option<Ruleset>) -> ZomeApiResult<JsonString> {
+        with_rules!(ruleset.unwrap_or_default(), R => {
+            Ok(default_to_json(R::describe_moves()))
+        })
     }
 
     #[zome_fn("hc_public")]
@@ -132,8 +277,20 @@ pub mod main {
     }
 
     #[zome_fn("hc_public")]
-    fn create_proposal(message: String) -> ZomeApiResult<Address> {
-        matchmaking::handle_create_proposal(message)
+    fn create_proposal(
+        message: String,
+        expires_at: Option<u64>,
+        invited: Option<Address>,
+        max_acceptances: Option<u32>,
+        settings: Option<GameSettings>,
+    ) -> ZomeApiResult<Address> {
+        matchmaking::handle_create_proposal(
+            message,
+            expires_at,
+            invited,
+            max_acceptances,
+            settings.unwrap_or_default(),
+        )
     }
 
     #[zome_fn("hc_public")]
@@ -142,10 +299,15 @@ pub mod main {
     }
 
     #[zome_fn("hc_public")]
-    fn accept_proposal(proposal_addr: Address, created_at: u32) -> ZomeApiResult<Address> {
+    fn accept_proposal(proposal_addr: Address, created_at: u64) -> ZomeApiResult<Address> {
         matchmaking::handle_accept_proposal(proposal_addr, created_at)
     }
 
+    #[zome_fn("hc_public")]
+    fn get_notifications() -> ZomeApiResult<Vec<Notification>> {
+        notifications::get_notifications()
+    }
+
     #[zome_fn("hc_public")]
     fn check_responses(proposal_addr: Address) -> ZomeApiResult<Vec<GetResponse<Game>>> {
         matchmaking::handle_check_responses(proposal_addr)
@@ -2,25 +2,78 @@ use hdk::{
     entry_definition::ValidatingEntryType,
     error::ZomeApiResult,
     holochain_core_types::{
-        dna::entry_types::Sharing, entry::Entry, link::LinkMatch, validation::EntryValidationData,
+        dna::entry_types::Sharing, entry::Entry, link::LinkMatch, time::Iso8601,
+        validation::EntryValidationData,
     },
     holochain_json_api::{
         error::JsonError,
         json::{default_to_json, JsonString},
     },
     holochain_persistence_api::cas::content::{Address, AddressableContent},
-    AGENT_ADDRESS,
+    LinkValidationData, AGENT_ADDRESS,
 };
 
 use serde::Serialize;
 use std::fmt::Debug;
 
-use crate::game::Game;
+use crate::error::to_zome_error;
+use crate::game::{self, Game};
+use crate::notifications;
+use crate::stats;
+use game_rules::{GameError, GameRules, Ruleset, TimeControl};
 
 #[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
 pub struct GameProposal {
     pub agent: Address,
     pub message: String,
+    /// No one can accept the proposal after this time, in milliseconds since the Unix epoch
+    pub expires_at: Option<u64>,
+    /// Only this agent can accept the proposal, anyone can when it is not set
+    pub invited: Option<Address>,
+    /// How many games can be started from the proposal. Any number can when it is not set, though
+    /// it stops being listed once the first has been.
+    pub max_acceptances: Option<u32>,
+    // proposals from before there were settings start a default game
+    #[serde(default)]
+    pub settings: GameSettings,
+}
+
+/// How a game started from a proposal is played, anything not set is the ruleset's default
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GameSettings {
+    #[serde(default)]
+    pub ruleset: Ruleset,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub win_length: Option<usize>,
+    pub time_control: Option<TimeControl>,
+    /// The agent accepting the proposal moves first, otherwise its author does
+    #[serde(default)]
+    pub acceptor_moves_first: bool,
+}
+
+impl GameSettings {
+    /// The game `acceptor` starts by accepting a proposal made by `proposer`
+    pub fn game(
+        &self,
+        acceptor: Address,
+        proposer: Address,
+        created_at: u64,
+        proposal: Option<Address>,
+    ) -> Game {
+        with_rules!(self.ruleset, R => Game {
+            player_1: acceptor,
+            player_2: proposer,
+            created_at,
+            ruleset: self.ruleset,
+            width: self.width.unwrap_or(R::DEFAULT_WIDTH),
+            height: self.height.unwrap_or(R::DEFAULT_HEIGHT),
+            win_length: self.win_length.unwrap_or(R::DEFAULT_WIN_LENGTH),
+            time_control: self.time_control,
+            player_1_moves_first: self.acceptor_moves_first,
+            proposal,
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -35,11 +88,21 @@ impl<T: Into<JsonString> + Debug + Serialize> From<GetResponse<T>> for JsonStrin
     }
 }
 
-pub fn handle_create_proposal(message: String) -> ZomeApiResult<Address> {
+pub fn handle_create_proposal(
+    message: String,
+    expires_at: Option<u64>,
+    invited: Option<Address>,
+    max_acceptances: Option<u32>,
+    settings: GameSettings,
+) -> ZomeApiResult<Address> {
     // create the data as a struct
     let game_proposal_data = GameProposal {
         agent: AGENT_ADDRESS.to_string().into(),
         message,
+        expires_at,
+        invited,
+        max_acceptances,
+        settings,
     };
 
     // create an entry
@@ -65,9 +128,13 @@ pub fn handle_create_proposal(message: String) -> ZomeApiResult<Address> {
     Ok(proposal_address)
 }
 
+/// Define the anchor entry again and compute its hash
+fn proposals_anchor_address() -> Address {
+    Entry::App("anchor".into(), "game_proposals".into()).address()
+}
+
 pub fn handle_get_proposals() -> ZomeApiResult<Vec<GetResponse<GameProposal>>> {
-    // define the anchor entry again and compute its hash
-    let anchor_address = Entry::App("anchor".into(), "game_proposals".into()).address();
+    let anchor_address = proposals_anchor_address();
 
     Ok(hdk::utils::get_links_and_load_type(
         &anchor_address,
@@ -85,27 +152,53 @@ pub fn handle_get_proposals() -> ZomeApiResult<Vec<GetResponse<GameProposal>>> {
     .collect())
 }
 
-pub fn handle_accept_proposal(proposal_addr: Address, created_at: u32) -> ZomeApiResult<Address> {
+pub fn handle_accept_proposal(proposal_addr: Address, created_at: u64) -> ZomeApiResult<Address> {
     // this will early return error if it doesn't exist
-    let proposal: GameProposal = hdk::utils::get_as_type(proposal_addr.clone())?;
+    let proposal: GameProposal = game::get_as(&proposal_addr, "game_proposal")?;
 
-    // create the new game
-    let game = Game {
-        player_1: AGENT_ADDRESS.to_string().into(),
-        player_2: proposal.agent,
+    // create the new game with the settings the proposer asked for
+    let game = proposal.settings.game(
+        AGENT_ADDRESS.to_string().into(),
+        proposal.agent.clone(),
         created_at,
-    };
-    let game_entry = Entry::App("game".into(), game.into());
-    let game_addr = hdk::commit_entry(&game_entry)?;
+        Some(proposal_addr.clone()),
+    );
+    let accepted = get_acceptances(&proposal_addr)?;
+    if is_full(&proposal, accepted.len()) {
+        return Err(to_zome_error(GameError::rule_violation(
+            "This proposal has already been accepted as many times as it allows",
+        )));
+    }
+    // validation checks the expiry against the time the game is committed, which is about now
+    let now = Iso8601::from((created_at / 1000) as i64);
+    is_valid_acceptance(&proposal_addr, &proposal, &game, &accepted, &now)
+        .map_err(|reason| to_zome_error(GameError::RuleViolation { reason }))?;
+    let game_addr = game::commit_game(game)?;
 
     // link to the proposal
     hdk::link_entries(&proposal_addr, &game_addr, "from_proposal", "")?;
+    notifications::notify_proposer(&proposal.agent, &proposal_addr, &game_addr)?;
+
+    // stop listing the proposal once it has been taken up
+    if is_taken(&proposal, accepted.len() + 1) {
+        hdk::remove_link(
+            &proposals_anchor_address(),
+            &proposal_addr,
+            "has_proposal",
+            "",
+        )?;
+    }
     Ok(game_addr)
 }
 
 pub fn handle_check_responses(proposal_addr: Address) -> ZomeApiResult<Vec<GetResponse<Game>>> {
+    get_acceptances(&proposal_addr)
+}
+
+/// The games that have been started from a proposal so far
+pub fn get_acceptances(proposal_addr: &Address) -> ZomeApiResult<Vec<GetResponse<Game>>> {
     Ok(hdk::utils::get_links_and_load_type(
-        &proposal_addr,
+        proposal_addr,
         LinkMatch::Exactly("from_proposal".into()),
         LinkMatch::Any,
     )?
@@ -120,10 +213,119 @@ pub fn handle_check_responses(proposal_addr: Address) -> ZomeApiResult<Vec<GetRe
     .collect())
 }
 
+/// Whether `game` can be started from the proposal at `proposal_addr`, when the games in
+/// `accepted` already have been. Expiry is judged by when the game was `committed_at` rather than
+/// the date the accepting agent gave it.
+///
+/// Validators only see the games that have reached them so far, so two agents accepting at about
+/// the same time can both be let in. To make every validator agree in the end, acceptances are
+/// ranked by date and then address and only the first `max_acceptances` are valid: a validator
+/// that has seen both games rejects whichever ranks later, whatever order they arrived in. Games
+/// can only be dated within `game::CLOCK_DRIFT_MS` of being committed, which limits how far an
+/// acceptor can jump the queue by backdating theirs.
+pub fn is_valid_acceptance(
+    proposal_addr: &Address,
+    proposal: &GameProposal,
+    game: &Game,
+    accepted: &[GetResponse<Game>],
+    committed_at: &Iso8601,
+) -> Result<(), String> {
+    if proposal
+        .invited
+        .iter()
+        .any(|invited| *invited != game.player_1)
+    {
+        return Err("This proposal is for another agent".into());
+    }
+    if proposal.expires_at.map_or(false, |expires_at| {
+        *committed_at > Iso8601::from((expires_at / 1000) as i64)
+    }) {
+        return Err("This proposal has expired".into());
+    }
+    let rank = |game: &Game, address: &Address| (game.created_at, address.to_string());
+    let game_address = Entry::App("game".into(), game.clone().into()).address();
+    let game_rank = rank(game, &game_address);
+    let accepted_before = accepted
+        .iter()
+        .filter(|other| rank(&other.entry, &other.address) < game_rank)
+        .count();
+    if is_full(proposal, accepted_before) {
+        return Err("This proposal has already been accepted as many times as it allows".into());
+    }
+    let expected = proposal.settings.game(
+        game.player_1.clone(),
+        proposal.agent.clone(),
+        game.created_at,
+        Some(proposal_addr.clone()),
+    );
+    if *game != expected {
+        return Err("The game does not match the players and settings of the proposal".into());
+    }
+    Ok(())
+}
+
+/// Whether a proposal that has been accepted `accepted` times can be accepted again
+fn is_full(proposal: &GameProposal, accepted: usize) -> bool {
+    proposal
+        .max_acceptances
+        .map_or(false, |max| accepted >= max as usize)
+}
+
+/// Whether a proposal that has been accepted `accepted` times should stop being listed, which is
+/// once it is full or, when it has no limit, once anyone has accepted it
+fn is_taken(proposal: &GameProposal, accepted: usize) -> bool {
+    match proposal.max_acceptances {
+        Some(_) => is_full(proposal, accepted),
+        None => accepted > 0,
+    }
+}
+
 pub fn handle_remove_proposal(proposal_addr: Address) -> ZomeApiResult<Address> {
+    let proposal: GameProposal = game::get_as(&proposal_addr, "game_proposal")?;
+    if proposal.agent.to_string() != AGENT_ADDRESS.to_string() {
+        return Err(to_zome_error(GameError::rule_violation(
+            "Only the author of a proposal can remove it",
+        )));
+    }
+    hdk::remove_link(
+        &proposals_anchor_address(),
+        &proposal_addr,
+        "has_proposal",
+        "",
+    )?;
     hdk::remove_entry(&proposal_addr)
 }
 
+/// Proposals are listed by their author, and only stop being listed when their author removes
+/// them or they have been taken up
+fn is_valid_listing(validation_data: LinkValidationData) -> Result<(), String> {
+    let (link, validation_data, is_removal) = match validation_data {
+        LinkValidationData::LinkAdd {
+            link,
+            validation_data,
+        } => (link, validation_data, false),
+        LinkValidationData::LinkRemove {
+            link,
+            validation_data,
+        } => (link, validation_data, true),
+    };
+    let proposal: GameProposal = game::get_as(link.link.target(), "game_proposal")
+        .map_err(|_| "Could not load the proposal during validation")?;
+    if validation_data.sources().contains(&proposal.agent) {
+        return Ok(());
+    }
+    if !is_removal {
+        return Err("Only the author of a proposal can list it".into());
+    }
+    let accepted = get_acceptances(link.link.target())
+        .map_err(|_| "Could not load the games already started during validation")?;
+    if is_taken(&proposal, accepted.len()) {
+        Ok(())
+    } else {
+        Err("Only the author of a proposal can remove it until it has been accepted".into())
+    }
+}
+
 pub fn game_proposal_def() -> ValidatingEntryType {
     entry!(
         // we will need to use this name when creating an entry later
@@ -141,15 +343,26 @@ pub fn game_proposal_def() -> ValidatingEntryType {
                 // only match if the entry is being created (not modified or deleted)
                 EntryValidationData::Create{ entry, validation_data } => {
                     let game_proposal = GameProposal::from(entry);
-                    if validation_data.sources().contains(&game_proposal.agent) {
+                    if !validation_data.sources().contains(&game_proposal.agent) {
+                        return Err("Cannot author a proposal from another agent".into())
+                    }
+                    if game_proposal.invited.as_ref() == Some(&game_proposal.agent) {
+                        return Err("Cannot invite yourself to a game".into())
+                    }
+                    if game_proposal.max_acceptances == Some(0) {
+                        return Err("A proposal must allow at least one game".into())
+                    }
+                    // the settings must make a game that can be played
+                    let settings = &game_proposal.settings;
+                    let game = settings.game(game_proposal.agent.clone(), game_proposal.agent, 0, None);
+                    with_rules!(settings.ruleset, R => R::is_valid_game(&game_rules::Game::from(&game)))
+                },
+                EntryValidationData::Delete{ old_entry, validation_data, .. } => {
+                    if validation_data.sources().contains(&old_entry.agent) {
                         Ok(())
                     } else {
-                        Err("Cannot author a proposal from another agent".into())
+                        Err("Only the author of a proposal can remove it".into())
                     }
-
-                },
-                EntryValidationData::Delete{..} => { // should update to only the author can delete
-                    Ok(())
                 },
                 _ => {
                     Err("Cannot modify, only create and delete".into())
@@ -161,8 +374,24 @@ pub fn game_proposal_def() -> ValidatingEntryType {
                 "game",
                 link_type: "from_proposal",
                 validation_package: || { hdk::ValidationPackageDefinition::Entry },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        // the game entry has already been checked against the proposal
+                        LinkValidationData::LinkAdd{link, validation_data} => {
+                            let new_game = game::get_game(link.link.target())
+                                .map_err(|_| "Could not load the game during validation")?;
+                            if new_game.proposal.as_ref() != Some(link.link.base()) {
+                                return Err("The game was not started from this proposal".into())
+                            }
+                            if !validation_data.sources().contains(&new_game.player_1) {
+                                return Err("Only the agent accepting a proposal can start its game".into())
+                            }
+                            Ok(())
+                        }
+                        LinkValidationData::LinkRemove{..} => {
+                            Err("Cannot remove a game from a proposal".into())
+                        }
+                    }
                 }
             )
         ]
@@ -185,8 +414,18 @@ pub fn anchor_def() -> ValidatingEntryType {
                 validation_package: || {
                     hdk::ValidationPackageDefinition::Entry
                 },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    is_valid_listing(validation_data)
+                }
+            ),
+            to!(
+                "game",
+                link_type: stats::FINISHED_GAME_LINK,
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData| {
+                    stats::is_valid_registration(validation_data)
                 }
             )
         ]
@@ -0,0 +1,122 @@
+use hdk::{
+    error::ZomeApiResult,
+    holochain_core_types::link::LinkMatch,
+    holochain_json_api::{error::JsonError, json::JsonString},
+    holochain_persistence_api::cas::content::Address,
+    LinkValidationData, AGENT_ADDRESS,
+};
+
+use crate::game;
+
+/// Games started from an agent's proposal are linked from that agent with this type, tagged with
+/// the proposal's address, until the agent has seen them
+pub const NOTIFICATION_LINK: &str = "notification";
+
+/// How long to wait for a proposer who is online to acknowledge a direct message. Sending blocks
+/// the acceptance, and the link is all an offline proposer needs, so this is kept short.
+const SEND_TIMEOUT_MS: usize = 1000;
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct Notification {
+    pub proposal: Address,
+    pub game: Address,
+    pub accepted_by: Address,
+}
+
+/// Let the author of a proposal know it has been accepted, both by leaving a notification
+/// for them to collect and by messaging them directly in case they are online now
+pub fn notify_proposer(
+    proposer: &Address,
+    proposal: &Address,
+    game_address: &Address,
+) -> ZomeApiResult<()> {
+    hdk::link_entries(
+        proposer,
+        game_address,
+        NOTIFICATION_LINK,
+        &proposal.to_string(),
+    )?;
+    let notification = Notification {
+        proposal: proposal.clone(),
+        game: game_address.clone(),
+        accepted_by: AGENT_ADDRESS.to_string().into(),
+    };
+    // the proposer might well be offline, the link above is all they really need
+    let _ = hdk::send(
+        proposer.clone(),
+        serde_json::to_string(&notification).unwrap_or_default(),
+        SEND_TIMEOUT_MS.into(),
+    );
+    Ok(())
+}
+
+/// Every notification this agent has not yet seen. Collecting them clears them, but only once
+/// they have all loaded, and a notification whose game cannot be loaded yet is left for next time.
+pub fn get_notifications() -> ZomeApiResult<Vec<Notification>> {
+    let me = Address::from(AGENT_ADDRESS.to_string());
+    let loaded: Vec<(Notification, String)> =
+        hdk::get_links(&me, LinkMatch::Exactly(NOTIFICATION_LINK), LinkMatch::Any)?
+            .links()
+            .into_iter()
+            .filter_map(|link| {
+                let game = game::get_game(&link.address).ok()?;
+                let notification = Notification {
+                    proposal: link.tag.clone().into(),
+                    game: link.address,
+                    accepted_by: game.player_1,
+                };
+                Some((notification, link.tag))
+            })
+            .collect();
+    Ok(loaded
+        .into_iter()
+        .map(|(notification, tag)| {
+            // a link that cannot be removed only means the notification is delivered again
+            let _ = hdk::remove_link(&me, &notification.game, NOTIFICATION_LINK, &tag);
+            notification
+        })
+        .collect())
+}
+
+/// A direct message from an agent who accepted one of our proposals, passed on to any UI
+/// listening for signals
+pub fn receive(from: Address, message: String) -> String {
+    match serde_json::from_str::<Notification>(&message) {
+        Ok(ref notification) if notification.accepted_by == from => {
+            let _ = hdk::emit_signal("proposal_accepted", JsonString::from_json(&message));
+            "received".into()
+        }
+        _ => "ignored".into(),
+    }
+}
+
+/// Only the agent who accepted a proposal can notify its author, and only that author can
+/// clear the notification
+pub fn is_valid_notification(validation_data: LinkValidationData) -> Result<(), String> {
+    match validation_data {
+        LinkValidationData::LinkAdd {
+            link,
+            validation_data,
+        } => {
+            let game = game::get_game(link.link.target())
+                .map_err(|_| "Could not load the game during validation")?;
+            if *link.link.base() != game.player_2 {
+                Err("Only the author of the proposal can be notified of its game".into())
+            } else if !validation_data.sources().contains(&game.player_1) {
+                Err("Only the agent who accepted the proposal can send its notification".into())
+            } else {
+                Ok(())
+            }
+        }
+        LinkValidationData::LinkRemove {
+            link,
+            validation_data,
+        } => {
+            if validation_data.sources().contains(link.link.base()) {
+                Ok(())
+            } else {
+                Err("Only the agent notified can clear a notification".into())
+            }
+        }
+    }
+}
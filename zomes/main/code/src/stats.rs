@@ -0,0 +1,158 @@
+use hdk::{
+    error::ZomeApiResult,
+    holochain_core_types::{entry::Entry, link::LinkMatch},
+    holochain_json_api::{error::JsonError, json::JsonString},
+    holochain_persistence_api::cas::content::{Address, AddressableContent},
+    LinkValidationData,
+};
+
+use game_rules::{rating::elo, rating::GameResult, GameRules, Outcome};
+
+use crate::game;
+
+/// Every finished game is linked from this anchor with `FINISHED_GAME_LINK`
+pub const FINISHED_GAMES_ANCHOR: &str = "finished_games";
+pub const FINISHED_GAME_LINK: &str = "finished_game";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, DefaultJson)]
+pub struct PlayerStats {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    /// Losses where this player resigned, these are counted in `losses` too
+    pub resignations: u32,
+}
+
+impl PlayerStats {
+    fn add(&mut self, player: &str, outcome: &Outcome) {
+        match outcome {
+            Outcome::Resigned { winner } if winner != player => {
+                self.losses += 1;
+                self.resignations += 1;
+            }
+            _ => match outcome.winner() {
+                Some(winner) if winner == player => self.wins += 1,
+                Some(_) => self.losses += 1,
+                None => self.draws += 1,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct LeaderboardEntry {
+    pub agent: Address,
+    pub rating: f64,
+    pub stats: PlayerStats,
+}
+
+fn anchor_address() -> Address {
+    Entry::App("anchor".into(), FINISHED_GAMES_ANCHOR.into()).address()
+}
+
+/// Record a game that has just ended so it counts towards its players' stats
+pub fn register_finished_game(game_address: &Address) -> ZomeApiResult<()> {
+    let anchor_entry = Entry::App("anchor".into(), FINISHED_GAMES_ANCHOR.into());
+    let anchor_address = hdk::commit_entry(&anchor_entry)?;
+    hdk::link_entries(&anchor_address, game_address, FINISHED_GAME_LINK, "")?;
+    Ok(())
+}
+
+/// The result of every finished game, in the order the games ended
+pub fn get_results() -> ZomeApiResult<Vec<GameResult>> {
+    let mut game_addresses = hdk::get_links(
+        &anchor_address(),
+        LinkMatch::Exactly(FINISHED_GAME_LINK),
+        LinkMatch::Any,
+    )?
+    .addresses();
+    // either player can register a game, so the same game can be linked more than once
+    game_addresses.sort_by_key(|address| address.to_string());
+    game_addresses.dedup();
+
+    let mut finished = Vec::new();
+    for game_address in game_addresses {
+        // a game that cannot be loaded right now is left out rather than hiding every result
+        if let Ok(Some((ended_at, result))) = get_result(&game_address) {
+            finished.push((ended_at, game_address.to_string(), result));
+        }
+    }
+    // ties are broken by address so every node replays the results in the same order
+    finished.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    Ok(finished.into_iter().map(|(_, _, result)| result).collect())
+}
+
+/// The result of a game and when its last move was made, or `None` if it has not ended
+fn get_result(game_address: &Address) -> ZomeApiResult<Option<(Option<u64>, GameResult)>> {
+    let game = game::get_game(game_address)?;
+    let moves = game::get_moves(game_address)?;
+    let outcome = with_rules!(game.ruleset, R => {
+        R::outcome(&game::state_from_moves::<R>(&game, &moves)?).clone()
+    });
+    // the registry only records that a game ended, the moves decide how
+    if !outcome.is_over() {
+        return Ok(None);
+    }
+    let result = GameResult {
+        player_1: game.player_1.to_string(),
+        player_2: game.player_2.to_string(),
+        outcome,
+    };
+    let ended_at = moves.last().map(|game_move| game_move.timestamp);
+    Ok(Some((ended_at, result)))
+}
+
+pub fn get_player_stats(agent: &Address) -> ZomeApiResult<PlayerStats> {
+    Ok(tally(&get_results()?, &agent.to_string()))
+}
+
+fn tally(results: &[GameResult], player: &str) -> PlayerStats {
+    let mut stats = PlayerStats::default();
+    results
+        .iter()
+        .filter(|result| result.player_1 == player || result.player_2 == player)
+        .for_each(|result| stats.add(player, &result.outcome));
+    stats
+}
+
+/// Every player who has finished a game, highest Elo rating first
+pub fn get_leaderboard() -> ZomeApiResult<Vec<LeaderboardEntry>> {
+    let results = get_results()?;
+    let mut leaderboard: Vec<LeaderboardEntry> = elo::ratings(&results)
+        .into_iter()
+        .map(|(agent, rating)| LeaderboardEntry {
+            stats: tally(&results, &agent),
+            agent: agent.into(),
+            rating,
+        })
+        .collect();
+    leaderboard.sort_by(|a, b| {
+        b.rating
+            .partial_cmp(&a.rating)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(leaderboard)
+}
+
+/// Only the players of a game can register it as finished, and only once it has ended
+pub fn is_valid_registration(validation_data: LinkValidationData) -> Result<(), String> {
+    match validation_data {
+        LinkValidationData::LinkAdd {
+            link,
+            validation_data,
+        } => {
+            let game = game::get_game(link.link.target())
+                .map_err(|_| "Could not load the game during validation")?;
+            let sources = validation_data.sources();
+            if !sources.contains(&game.player_1) && !sources.contains(&game.player_2) {
+                return Err("Only the players of a game can register its result".into());
+            }
+            match get_result(link.link.target()) {
+                Ok(Some(_)) => Ok(()),
+                Ok(None) => Err("Only a game that has ended can be registered as finished".into()),
+                Err(_) => Err("Could not load the moves during validation".into()),
+            }
+        }
+        LinkValidationData::LinkRemove { .. } => Err("Cannot remove a finished game".into()),
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test_hooks)]
+use hdk::holochain_core_types::entry::Entry;
+use hdk::{error::ZomeApiResult, holochain_persistence_api::cas::content::Address};
+
+use crate::game_move::Move;
+
+/**
+ *
+ * Hooks that commit entries and links exactly as they are given, skipping every check the zome
+ * functions make first, so the tests can show validation alone stops what an agent running
+ * modified code could commit. They are only built into a DNA packaged for the tests with
+ * `RUSTFLAGS="--cfg test_hooks" hc package`, in any other DNA they refuse to do anything.
+ *
+ */
+#[cfg(test_hooks)]
+pub fn commit_move(new_move: Move) -> ZomeApiResult<Address> {
+    hdk::commit_entry(&Entry::App("move".into(), new_move.into()))
+}
+
+#[cfg(test_hooks)]
+pub fn link(
+    base: &Address,
+    target: &Address,
+    link_type: &str,
+    tag: &str,
+) -> ZomeApiResult<Address> {
+    hdk::link_entries(base, target, link_type, tag)
+}
+
+#[cfg(test_hooks)]
+pub fn register_finished_game(game_address: &Address) -> ZomeApiResult<()> {
+    crate::stats::register_finished_game(game_address)
+}
+
+#[cfg(not(test_hooks))]
+pub fn commit_move(_new_move: Move) -> ZomeApiResult<Address> {
+    Err(disabled())
+}
+
+#[cfg(not(test_hooks))]
+pub fn link(_base: &Address, _target: &Address, _type: &str, _tag: &str) -> ZomeApiResult<Address> {
+    Err(disabled())
+}
+
+#[cfg(not(test_hooks))]
+pub fn register_finished_game(_game_address: &Address) -> ZomeApiResult<()> {
+    Err(disabled())
+}
+
+#[cfg(not(test_hooks))]
+fn disabled() -> hdk::error::ZomeApiError {
+    hdk::error::ZomeApiError::Internal("This DNA was not built with test hooks".into())
+}